@@ -6,6 +6,7 @@
 
 
 @group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> material_color: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var<uniform> wind_strength: f32;
 
 @fragment
 fn fragment(input: VertexOutput, @builtin(front_facing) is_front: bool) -> @location(0) vec4<f32> {
@@ -19,7 +20,8 @@ fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
 
     let time = globals.time * 3.5;
-    let influence = saturate(vertex.position.x+0.5);
+    // a calm flag hangs almost still, strong wind makes it flap
+    let influence = saturate(vertex.position.x+0.5) * (0.15 + wind_strength);
     let main_wave = sin(time + vertex.position.x * 2.0) * 0.15;
 
     let flap = sin(time * 2.5 + vertex.position.x * 12.0) * 0.04;
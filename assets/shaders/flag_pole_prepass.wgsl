@@ -3,6 +3,7 @@
 #import bevy_pbr::prepass_io::VertexOutput
 
 @group(0) @binding(1) var<uniform> globals: Globals;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var<uniform> wind_strength: f32;
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
@@ -14,7 +15,8 @@ fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
 
     let time = globals.time * 3.5;
-    let influence = saturate(vertex.position.x+0.5);
+    // a calm flag hangs almost still, strong wind makes it flap
+    let influence = saturate(vertex.position.x+0.5) * (0.15 + wind_strength);
     let main_wave = sin(time + vertex.position.x * 2.0) * 0.15;
 
     let flap = sin(time * 2.5 + vertex.position.x * 12.0) * 0.04;
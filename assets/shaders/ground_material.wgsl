@@ -15,81 +15,122 @@
 }
 #endif
 
-const COURSE_WIDTH: f32 = 25.0;
+// sizes of the zones, keep in sync with shaping.rs
+const GREEN_RADIUS: f32 = 15.0;
+const FRINGE_WIDTH: f32 = 1.5;
+const TEE_HALF_LENGTH: f32 = 6.0;
+const TEE_HALF_WIDTH: f32 = 4.0;
+const FIRST_CUT_WIDTH: f32 = 2.5;
+const ROUGH_WIDTH: f32 = 10.0;
+// keep in sync with course_layout.rs
+const PATH_SAMPLES: u32 = 32u;
+
+// kinds of hazards, keep in sync with HazardKind
+const HAZARD_BUNKER: u32 = 0u;
+const HAZARD_POND: u32 = 1u;
+const HAZARD_WASTE_AREA: u32 = 2u;
+const NO_HAZARD: u32 = 0xffffffffu;
 
 struct GroundMaterial {
-    course: Polynomial,
-    bunker: Bunker,
-    start_x: f32,
-    end_x: f32,
+    course: CourseLayout,
+    style: GroundStyle,
 }
 
-struct Bunker {
+// how the biome of the course colours the ground, outside the course ground above the snow
+// line is snow and ground steeper than the rock slope is rock
+struct GroundStyle {
+    tint: vec4<f32>,
+    snow_line: f32,
+    rock_slope: f32,
+}
+
+// the course spline sampled into a polyline, x, z, half width and arc length per sample
+struct CourseLayout {
+    samples: array<vec4<f32>, PATH_SAMPLES>,
+    count: u32,
+}
+
+struct Hazard {
+    kind: u32,
     x: f32,
     y: f32,
     rot: f32,
     size: f32,
 }
 
-struct Polynomial {
-    a: f32,
-    b: f32,
-    c: f32,
-    d: f32,
-}
-
 @group(#{MATERIAL_BIND_GROUP}) @binding(100)
 var<uniform> ground_material: GroundMaterial;
-
-fn f(polynomial: Polynomial, x: f32) -> f32 {
-    return polynomial.a * x * x * x + polynomial.b * x * x + polynomial.c * x + polynomial.d;
+// every hazard touching the chunk, hazards of size 0 are padding
+@group(#{MATERIAL_BIND_GROUP}) @binding(101)
+var<storage, read> hazards: array<Hazard>;
+
+// How far outside the fairway a point lies, negative on it. Reads the samples straight from
+// the uniform, naga only allows constant indices into arrays held by value.
+fn distance_to_fairway(p: vec2<f32>) -> f32 {
+    // same as the generator: compare to the width at the closest point of the path
+    var closest_distance = 1e20;
+    var closest_half_width = 0.0;
+    for (var i = 1u; i < ground_material.course.count; i++) {
+        let a = ground_material.course.samples[i - 1u];
+        let b = ground_material.course.samples[i];
+        let along = b.xy - a.xy;
+        let t = clamp(dot(p - a.xy, along) / max(dot(along, along), 1e-6), 0.0, 1.0);
+        let d = distance(p, a.xy + along * t);
+        if d < closest_distance {
+            closest_distance = d;
+            closest_half_width = mix(a.z, b.z, t);
+        }
+    }
+    return closest_distance - closest_half_width;
 }
 
-fn f_prime(polynomial: Polynomial, x: f32) -> f32 {
-    return 3.0 * polynomial.a * x * x + 2.0 * polynomial.b * x + polynomial.c;
+fn on_tee(p: vec2<f32>) -> bool {
+    let start = ground_material.course.samples[0].xy;
+    let direction = normalize(ground_material.course.samples[1].xy - start);
+    let offset = p - start;
+    let along = dot(offset, direction);
+    let across = dot(offset, vec2<f32>(-direction.y, direction.x));
+    return abs(along) <= TEE_HALF_LENGTH && abs(across) <= TEE_HALF_WIDTH;
 }
 
-fn approx_distance_to_curve(polynomial: Polynomial, p: vec2<f32>) -> f32 {
-    let p_y = f(polynomial, p.x);
-    let p_d = f_prime(polynomial, p.x);
-    let h = sqrt(1 + p_d * p_d);
-    return abs((p_y - p.y) / h);
-}
+fn in_hazard(hazard: Hazard, p: vec2<f32>) -> bool {
+    let x = hazard.x - p.x;
+    let y = hazard.y - p.y;
 
-fn on_clean_grass(polynomial: Polynomial, p: vec2<f32>) -> bool {
-    if 0.0 < p.x && p.x < 300.0 {
-        return approx_distance_to_curve(polynomial, p) < COURSE_WIDTH;
-    } else if p.x <= 0.0 {
-        let p_d = f_prime(polynomial, 0.0);
-        let h = sqrt(1 + p_d * p_d);
-        let dy = f(polynomial, 0.0) - (p.y - p.x * p_d);
-        return sqrt(dy * dy + p.x * p.x) < COURSE_WIDTH * h;
-    } else {
-        let p_d = f_prime(polynomial, 300.0);
-        let h = sqrt(1 + p_d * p_d);
-        let dx = p.x - 300.0;
-        let dy = f(polynomial, 300.0) - (p.y - (p.x - 300.0) * p_d);
-        return sqrt(dy * dy + dx * dx) < COURSE_WIDTH * h;
-    }
-}
+    let s = sin(hazard.rot);
+    let c = cos(hazard.rot);
 
-fn in_bunker(bunker: Bunker, p: vec2<f32>) -> bool {
-    let x = bunker.x - p.x;
-    let y = bunker.y - p.y;
+    let rx = (x * c + y * s) / hazard.size;
+    let ry = (y * c - x * s) / hazard.size * 1.6;
 
-    let s = sin(bunker.rot);
-    let c = cos(bunker.rot);
+    return rx * rx + ry * ry < 1.0;
+}
 
-    let rx = (x * c + y * s) / bunker.size;
-    let ry = (y * c - x * s) / bunker.size * 1.6;
+// the hazard the point lies in, bunkers win over waste areas, ponds are drawn by the water
+fn hazard_at(p: vec2<f32>) -> u32 {
+    var result = NO_HAZARD;
+    for (var i = 0u; i < arrayLength(&hazards); i++) {
+        let hazard = hazards[i];
+        if hazard.size <= 0.0 || hazard.kind == HAZARD_POND || !in_hazard(hazard, p) {
+            continue;
+        }
+        if hazard.kind == HAZARD_BUNKER {
+            return HAZARD_BUNKER;
+        }
+        result = hazard.kind;
+    }
+    return result;
+}
 
-    return rx * rx + ry * ry < 1.0;
+fn distance_to_hole(p: vec2<f32>) -> f32 {
+    let hole = ground_material.course.samples[ground_material.course.count - 1u].xy;
+    return distance(hole, p);
 }
 
-fn in_hole(polynomial: Polynomial, p: vec2<f32>) -> bool {
-    let dx = 300.0 - p.x;
-    let dy = f(polynomial, 300.0) - p.y;
-    return dx * dx + dy * dy < 0.025;
+// rise per run of the ground with the given normal
+fn slope(normal: vec3<f32>) -> f32 {
+    let n = normalize(normal);
+    return length(n.xz) / max(n.y, 0.001);
 }
 
 @fragment
@@ -107,20 +148,37 @@ fn fragment(
     var out: FragmentOutput;
     // apply lighting
     out.color = apply_pbr_lighting(pbr_input);
+    out.color = vec4<f32>(out.color.rgb * ground_material.style.tint.rgb, out.color.a);
 
-    let polynomial = ground_material.course;
-
-    if in_bunker(ground_material.bunker, in.world_position.xz) {
+    let hazard = hazard_at(in.world_position.xz);
+    if hazard == HAZARD_BUNKER {
         out.color.b = 0.0;
         out.color.r = out.color.g;
         out.color.g *= 0.8;
-    } else if !on_clean_grass(polynomial, in.world_position.xz) {
-        // we can optionally modify the lit color before post-processing is applied
-        out.color = vec4<f32>(out.color.rgb * 0.65, out.color.a);
-    }
-
-    if in_hole(polynomial, in.world_position.xz) {
-        out.color.a = 0.0;
+    } else if hazard == HAZARD_WASTE_AREA {
+        // patchy sand with some grass showing through
+        out.color = vec4<f32>(vec3<f32>(out.color.g * 0.95, out.color.g * 0.85, out.color.b * 0.5), out.color.a);
+    } else if on_tee(in.world_position.xz) {
+        out.color = vec4<f32>(out.color.rgb * vec3<f32>(0.95, 1.1, 0.95), out.color.a);
+    } else if distance_to_hole(in.world_position.xz) < GREEN_RADIUS {
+        // closely mown putting surface
+        out.color = vec4<f32>(out.color.rgb * vec3<f32>(0.85, 1.15, 0.85), out.color.a);
+    } else if distance_to_hole(in.world_position.xz) < GREEN_RADIUS + FRINGE_WIDTH {
+        out.color = vec4<f32>(out.color.rgb * vec3<f32>(0.9, 1.05, 0.9), out.color.a);
+    } else {
+        // every cut of grass away from the fairway gets darker
+        let edge = distance_to_fairway(in.world_position.xz);
+        if edge >= FIRST_CUT_WIDTH + ROUGH_WIDTH && in.world_position.y > ground_material.style.snow_line {
+            out.color = vec4<f32>(vec3<f32>(out.color.g * 1.8, out.color.g * 1.85, out.color.g * 1.95), out.color.a);
+        } else if edge >= FIRST_CUT_WIDTH + ROUGH_WIDTH && slope(in.world_normal) > ground_material.style.rock_slope {
+            out.color = vec4<f32>(vec3<f32>(out.color.g * 0.75, out.color.g * 0.72, out.color.g * 0.7), out.color.a);
+        } else if edge >= FIRST_CUT_WIDTH + ROUGH_WIDTH {
+            out.color = vec4<f32>(out.color.rgb * 0.65, out.color.a);
+        } else if edge >= FIRST_CUT_WIDTH {
+            out.color = vec4<f32>(out.color.rgb * 0.75, out.color.a);
+        } else if edge >= 0.0 {
+            out.color = vec4<f32>(out.color.rgb * 0.88, out.color.a);
+        }
     }
 
     // apply in-shader post processing (fog, alpha-premultiply, and also tonemapping, debanding if the camera is non-hdr)
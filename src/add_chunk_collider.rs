@@ -1,36 +1,43 @@
-use avian3d::prelude::{Collider, CollisionMargin, RigidBody};
+use avian3d::prelude::{Collider, RigidBody};
 use bevy::prelude::*;
 
-use crate::chunk::Chunk;
+use crate::chunk::CHUNK_SIZE_METERS;
+use crate::chunk::cup::CupGeometry;
+use crate::objects::cup::spawn_cup_colliders;
 
+// The collider of a chunk. It is a separate entity in world space, so it is in place as soon
+// as the chunk is generated, while the mesh is still being lifted into view.
 #[derive(Component)]
-pub struct HasCollider;
+pub struct HasCollider(pub Entity);
 
-pub fn create_collider_from_mesh(
-    mut commands: Commands,
-    query: Query<(Entity, &Mesh3d), (With<Chunk>, Without<HasCollider>)>,
-    mesh_assets: Res<Assets<Mesh>>,
-) {
-    for (entity, mesh) in query {
-        let mesh_id = mesh.id();
-        let chunk_mesh = mesh_assets.get(mesh_id).unwrap();
-
-        let collider = commands
-            .spawn((
-                Collider::trimesh_from_mesh(chunk_mesh).unwrap(),
-                CollisionMargin(10.0),
-                Transform::from_xyz(0.0, -10.0, 0.0),
-                RigidBody::Static,
-                // Friction {
-                //     static_coefficient: 100000.0,
-                //     dynamic_coefficient: 1.0,
-                //     combine_rule: CoefficientCombine::Multiply,
-                // },
-            ))
-            .id();
-        commands
-            .entity(entity)
-            .insert(HasCollider)
-            .add_child(collider);
+// `cup` only for the chunk containing the hole
+pub fn spawn_chunk_collider(
+    commands: &mut Commands,
+    collider: Collider,
+    world_offset: [i32; 2],
+    cup: Option<&CupGeometry>,
+) -> Entity {
+    let half_size = CHUNK_SIZE_METERS as f32 * 0.5;
+    let center = Vec3::new(
+        world_offset[0] as f32 + half_size,
+        0.0,
+        world_offset[1] as f32 + half_size,
+    );
+    let mut entity = commands.spawn((
+        collider,
+        Transform::from_translation(center),
+        RigidBody::Static,
+        // Friction {
+        //     static_coefficient: 100000.0,
+        //     dynamic_coefficient: 1.0,
+        //     combine_rule: CoefficientCombine::Multiply,
+        // },
+    ));
+    // the cup is cut out of the heightfield, so its walls and sensor go in together with it
+    if let Some(cup) = cup {
+        entity.with_children(|builder| {
+            spawn_cup_colliders(builder, cup, Transform::from_translation(-center));
+        });
     }
+    entity.id()
 }
@@ -0,0 +1,356 @@
+use crate::chunk::{Hazard, HazardKind};
+use crate::generation::{Prop, PropType};
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// chunks kept in memory after they were unloaded, about 70 kB each at the finest level
+const MEMORY_CAPACITY: usize = 512;
+const MAGIC: &[u8; 4] = b"TKCH";
+// bump whenever the layout below changes, or chunks are built differently from the heights of
+// their generator, so older files are thrown away instead of being loaded; changes to a single
+// generator bump its `TerrainGenerator::version` instead
+const FORMAT_VERSION: u32 = 2;
+
+// The part of a chunk that is expensive to generate. It only depends on the course and the
+// position and resolution of the chunk, everything else is taken from the generator.
+#[derive(Clone)]
+pub struct ChunkTerrain {
+    pub resolution: usize,
+    pub elevation: Vec<f32>,
+    // from central differences of the generator, four more height samples per vertex
+    pub normals: Vec<[f32; 3]>,
+    pub props: Vec<Prop>,
+    pub hazards: Vec<Hazard>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ChunkKey {
+    // generator name, options, version and seed, see `course_name`
+    pub course: Arc<str>,
+    pub chunk_pos: (i32, i32),
+    // the resolution generated, see `ChunkTerrain::resolution_for`
+    pub resolution: usize,
+}
+
+// Names a course for the cache, e.g. `island_max_carry=120-v1-1718000000`.
+pub fn course_name(generator: &str, version: u32, seed: u32) -> Arc<str> {
+    let generator = generator
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '=' | '.' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+    format!("{generator}-v{version}-{seed}").into()
+}
+
+// Where `--chunk-cache <dir>` asks to keep chunks on disk, set by `apply_command_line`.
+#[derive(Resource, Default)]
+pub struct ChunkCacheDirectory(pub Option<PathBuf>);
+
+// Least recently used chunks in memory and, with `--chunk-cache <dir>`, every chunk ever
+// generated on disk.
+pub struct ChunkCache {
+    // with the tick they were last used at
+    memory: HashMap<ChunkKey, (Arc<ChunkTerrain>, u64)>,
+    // the same chunks by the tick they were last used at, oldest first
+    recency: BTreeMap<u64, ChunkKey>,
+    tick: u64,
+    directory: Option<PathBuf>,
+}
+
+impl ChunkCache {
+    pub fn new(directory: Option<PathBuf>) -> Self {
+        ChunkCache {
+            memory: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            directory,
+        }
+    }
+
+    pub fn get(&mut self, key: &ChunkKey) -> Option<Arc<ChunkTerrain>> {
+        self.tick += 1;
+        let (terrain, last_used) = self.memory.get_mut(key)?;
+        let key = self.recency.remove(last_used)?;
+        *last_used = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(terrain.clone())
+    }
+
+    pub fn insert(&mut self, key: ChunkKey, terrain: Arc<ChunkTerrain>) {
+        self.tick += 1;
+        if let Some((_, last_used)) = self.memory.insert(key.clone(), (terrain, self.tick)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(self.tick, key);
+        if self.memory.len() > MEMORY_CAPACITY
+            && let Some((_, oldest)) = self.recency.pop_first()
+        {
+            self.memory.remove(&oldest);
+        }
+    }
+
+    // where the chunk is stored on disk, if at all
+    pub fn file(&self, key: &ChunkKey) -> Option<PathBuf> {
+        let (x, z) = key.chunk_pos;
+        self.directory.as_ref().map(|directory| {
+            directory
+                .join(&*key.course)
+                .join(format!("{x}_{z}_{}.chunk", key.resolution))
+        })
+    }
+}
+
+// `None` if there is no such file, or an unreadable or stale one, which is removed
+pub fn load(path: &Path) -> Option<ChunkTerrain> {
+    let bytes = fs::read(path).ok()?;
+    let terrain = decode(&bytes);
+    if terrain.is_none() {
+        info!("discarding stale chunk cache entry {}", path.display());
+        let _ = fs::remove_file(path);
+    }
+    terrain
+}
+
+pub fn store(path: &Path, terrain: &ChunkTerrain) {
+    // written next to it first, so a chunk is never read half written
+    let temporary = path.with_extension("tmp");
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&temporary, encode(terrain)))
+        .and_then(|_| fs::rename(&temporary, path));
+    if let Err(error) = result {
+        warn!("could not cache chunk at {}: {error}", path.display());
+    }
+}
+
+// Little endian: magic, version, resolution, then every list with its length in front.
+fn encode(terrain: &ChunkTerrain) -> Vec<u8> {
+    let mut writer = Writer(Vec::new());
+    writer.0.extend_from_slice(MAGIC);
+    writer.u32(FORMAT_VERSION);
+    writer.u32(terrain.resolution as u32);
+
+    writer.u32(terrain.elevation.len() as u32);
+    for height in &terrain.elevation {
+        writer.f32(*height);
+    }
+    writer.u32(terrain.normals.len() as u32);
+    for normal in &terrain.normals {
+        normal.iter().for_each(|component| writer.f32(*component));
+    }
+
+    writer.u32(terrain.props.len() as u32);
+    for prop in &terrain.props {
+        writer.u8(match prop.prop_type {
+            PropType::Tree => 0,
+            PropType::Scrub => 1,
+        });
+        writer.f32(prop.position.0);
+        writer.f32(prop.position.1);
+        writer.f32(prop.position.2);
+        writer.u32(prop.seed);
+    }
+
+    writer.u32(terrain.hazards.len() as u32);
+    for hazard in &terrain.hazards {
+        writer.u8(hazard.kind as u8);
+        writer.f32(hazard.x);
+        writer.f32(hazard.y);
+        writer.f32(hazard.rot);
+        writer.f32(hazard.size);
+    }
+    writer.0
+}
+
+fn decode(bytes: &[u8]) -> Option<ChunkTerrain> {
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len())? != MAGIC || reader.u32()? != FORMAT_VERSION {
+        return None;
+    }
+    let resolution = reader.u32()? as usize;
+    let vertices = (resolution + 1) * (resolution + 1);
+
+    let elevation = reader.list(|reader| reader.f32())?;
+    let normals = reader.list(|reader| Some([reader.f32()?, reader.f32()?, reader.f32()?]))?;
+    if elevation.len() != vertices || normals.len() != vertices {
+        return None;
+    }
+
+    let props = reader.list(|reader| {
+        let prop_type = match reader.u8()? {
+            0 => PropType::Tree,
+            1 => PropType::Scrub,
+            _ => return None,
+        };
+        Some(Prop {
+            prop_type,
+            position: (reader.f32()?, reader.f32()?, reader.f32()?),
+            seed: reader.u32()?,
+        })
+    })?;
+
+    let hazards = reader.list(|reader| {
+        let kind = match reader.u8()? {
+            0 => HazardKind::Bunker,
+            1 => HazardKind::Pond,
+            2 => HazardKind::WasteArea,
+            _ => return None,
+        };
+        Some(Hazard {
+            kind,
+            x: reader.f32()?,
+            y: reader.f32()?,
+            rot: reader.f32()?,
+            size: reader.f32()?,
+        })
+    })?;
+
+    // trailing bytes mean the file is not what this version writes
+    reader.0.is_empty().then_some(ChunkTerrain {
+        resolution,
+        elevation,
+        normals,
+        props,
+        hazards,
+    })
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.0.len() < count {
+            return None;
+        }
+        let (result, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Some(result)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        let count = self.u32()? as usize;
+        // a corrupted length must not allocate gigabytes
+        if count > self.0.len() {
+            return None;
+        }
+        (0..count).map(|_| item(self)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terrain() -> ChunkTerrain {
+        let resolution = 2;
+        let vertices = (resolution + 1) * (resolution + 1);
+        ChunkTerrain {
+            resolution,
+            elevation: (0..vertices).map(|i| i as f32 * 0.5 - 1.0).collect(),
+            normals: (0..vertices).map(|i| [0.1, 1.0, i as f32 * 0.01]).collect(),
+            props: vec![Prop {
+                prop_type: PropType::Scrub,
+                position: (3.0, 1.5, 7.25),
+                seed: 42,
+            }],
+            hazards: vec![Hazard {
+                kind: HazardKind::WasteArea,
+                x: 12.0,
+                y: -4.5,
+                rot: 0.75,
+                size: 9.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let bytes = encode(&terrain());
+        let decoded = decode(&bytes).expect("own encoding must decode");
+        assert_eq!(decoded.resolution, 2);
+        assert_eq!(decoded.elevation, terrain().elevation);
+        assert_eq!(decoded.normals, terrain().normals);
+        assert_eq!(decoded.props[0].position, (3.0, 1.5, 7.25));
+        assert_eq!(decoded.props[0].seed, 42);
+        assert_eq!(decoded.hazards[0].kind, HazardKind::WasteArea);
+        assert_eq!(decoded.hazards[0].rot, 0.75);
+        assert_eq!(encode(&decoded), bytes);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = encode(&terrain());
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(decode(&bytes).is_none());
+    }
+
+    #[test]
+    fn rejects_trailing_and_missing_bytes() {
+        let mut bytes = encode(&terrain());
+        bytes.push(0);
+        assert!(decode(&bytes).is_none());
+        bytes.truncate(bytes.len() - 2);
+        assert!(decode(&bytes).is_none());
+    }
+
+    #[test]
+    fn drops_the_least_recently_used_chunk() {
+        let key = |x| ChunkKey {
+            course: course_name("links", 1, 7),
+            chunk_pos: (x, 0),
+            resolution: 2,
+        };
+        let mut cache = ChunkCache::new(None);
+        for x in 0..MEMORY_CAPACITY as i32 {
+            cache.insert(key(x), Arc::new(terrain()));
+        }
+        // the first chunk was used again, so the second one is the oldest
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(-1), Arc::new(terrain()));
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.get(&key(-1)).is_some());
+        assert_eq!(cache.memory.len(), MEMORY_CAPACITY);
+        assert_eq!(cache.recency.len(), MEMORY_CAPACITY);
+    }
+
+    #[test]
+    fn generator_versions_name_different_courses() {
+        assert_eq!(&*course_name("links", 1, 7), "links-v1-7");
+        assert_ne!(course_name("links", 1, 7), course_name("links", 2, 7));
+    }
+}
@@ -1,28 +1,73 @@
+use crate::add_chunk_collider::{HasCollider, spawn_chunk_collider};
 use crate::animation::LiftDownAnimation;
+use crate::chunk::cache::{self, ChunkCache, ChunkKey, ChunkTerrain, course_name};
 use crate::chunk::chunk_loader::ChunkLoader;
+use crate::chunk::generation::{Remesh, cup_in_chunk};
+use crate::chunk::lod::{COLLIDER_RESOLUTION, resolution_for};
 use crate::chunk::{CHUNK_SIZE_METERS, Chunk, ToUnload};
-use bevy::prelude::{Commands, Component, Entity, Query, ResMut, Resource, Transform};
+use crate::generation::TerrainGenerator;
+use avian3d::prelude::Collider;
+use bevy::prelude::{
+    Commands, Component, Entity, Mesh3d, Query, ResMut, Resource, Transform, With,
+};
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
-use crate::generation::grasslands::GrasslandsGenerator;
-use crate::generation::TerrainGenerator;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Resource)]
 pub struct ChunkManager {
     chunks: HashMap<(i32, i32), Entity>,
-    pub generator: Box<dyn TerrainGenerator + Send + Sync>,
+    // chunks still being generated in the background
+    pending: HashSet<(i32, i32)>,
+    // grid resolution each chunk was last asked for
+    resolutions: HashMap<(i32, i32), usize>,
+    // chunks whose collider is in place, or about to be with the next commands
+    colliders: HashSet<(i32, i32)>,
+    // shared with the generation tasks
+    pub generator: Arc<dyn TerrainGenerator + Send + Sync>,
+    // generated terrain of this and earlier courses
+    cache: ChunkCache,
+    // names the course of `generator` in the cache
+    course: Arc<str>,
+    // counts the generators, as two courses can share a seed
+    course_id: u32,
 }
 
 impl ChunkManager {
-    pub fn new(seed: u32) -> Self {
+    // `variant` is the `GeneratorRegistry::variant` the generator was built from
+    pub fn new(
+        generator: Box<dyn TerrainGenerator + Send + Sync>,
+        variant: &str,
+        cache: ChunkCache,
+    ) -> Self {
         ChunkManager {
             chunks: HashMap::new(),
-            generator: Box::new(GrasslandsGenerator::new(seed)),
+            pending: HashSet::new(),
+            resolutions: HashMap::new(),
+            colliders: HashSet::new(),
+            course: course_name(variant, generator.version(), generator.seed()),
+            generator: generator.into(),
+            cache,
+            course_id: 0,
         }
     }
 
-    pub fn replace_generator(&mut self, commands: &mut Commands, generator: Box<dyn TerrainGenerator + Send + Sync>) -> Box<dyn TerrainGenerator + Send + Sync> {
-        let result = std::mem::replace(&mut self.generator, generator);
+    // changes with every `replace_generator`
+    pub fn course_id(&self) -> u32 {
+        self.course_id
+    }
+
+    pub fn replace_generator(
+        &mut self,
+        commands: &mut Commands,
+        generator: Box<dyn TerrainGenerator + Send + Sync>,
+        variant: &str,
+    ) -> Arc<dyn TerrainGenerator + Send + Sync> {
+        self.course = course_name(variant, generator.version(), generator.seed());
+        self.course_id += 1;
+        let result = std::mem::replace(&mut self.generator, generator.into());
         for chunk_pos in self.chunks.keys().cloned().collect::<Vec<_>>() {
             self.unload_chunk(commands, chunk_pos);
         }
@@ -30,40 +75,208 @@ impl ChunkManager {
     }
 
     pub fn height_at(&self, chunks: Query<(Entity, &Chunk)>, x: f32, z: f32) -> Option<f32> {
-        let chunk_pos = (
+        let chunk_id = self.chunks.get(&ChunkManager::chunk_pos_at(x, z))?;
+        let chunk = chunks.get(*chunk_id).ok()?.1;
+
+        chunk.height_at(
+            x.rem_euclid(CHUNK_SIZE_METERS as f32),
+            z.rem_euclid(CHUNK_SIZE_METERS as f32),
+        )
+    }
+
+    pub fn chunk_pos_at(x: f32, z: f32) -> (i32, i32) {
+        (
             (x / CHUNK_SIZE_METERS as f32).floor() as i32,
             (z / CHUNK_SIZE_METERS as f32).floor() as i32,
-        );
+        )
+    }
 
-        let chunk_id = self.chunks.get(&chunk_pos)?;
-        let chunk = chunks.get(*chunk_id).ok()?.1;
+    // whether the collider of the chunk is in place, or about to be with the next commands
+    pub fn has_collider(&self, chunk_pos: (i32, i32)) -> bool {
+        self.colliders.contains(&chunk_pos)
+    }
 
-        chunk.height_at(x % CHUNK_SIZE_METERS as f32, z % CHUNK_SIZE_METERS as f32)
+    // Puts the collider of the chunk in place right away, for when it is needed sooner than a
+    // background task could be done. Only its heights are sampled on this thread, the rest of
+    // the chunk is still generated in the background, and meshed before all other chunks.
+    pub fn force_chunk(&mut self, commands: &mut Commands, chunk_pos: (i32, i32)) {
+        if self.has_collider(chunk_pos) {
+            return;
+        }
+        // chunks the loaders don't know yet are beyond their reach, where they would pick the
+        // coarsest level
+        if !self.chunks.contains_key(&chunk_pos) {
+            self.load_chunk(commands, chunk_pos, f32::INFINITY);
+        }
+
+        let generator = self.generator.clone();
+        let world_offset = world_offset(chunk_pos);
+        let collider = match self.look_up(chunk_pos, COLLIDER_RESOLUTION).cached {
+            Some(terrain) => terrain.collider(generator.as_ref(), world_offset),
+            None => ChunkTerrain::sample_collider(generator.as_ref(), world_offset),
+        };
+        let cup = cup_in_chunk(generator.as_ref(), world_offset);
+        let collider = spawn_chunk_collider(commands, collider, world_offset, cup.as_ref());
+        commands
+            .entity(self.chunks[&chunk_pos])
+            .insert((HasCollider(collider), MeshGenerationPriority(0.0)));
+        self.colliders.insert(chunk_pos);
     }
 
-    fn load_chunk(&mut self, commands: &mut Commands, chunk_pos: (i32, i32), priority: f32) {
-        self.chunks.entry(chunk_pos).or_insert_with(|| {
-            commands
-                .spawn((
-                    Chunk::generate_at(self.generator.as_ref(), [
-                        chunk_pos.0 * CHUNK_SIZE_METERS as i32,
-                        chunk_pos.1 * CHUNK_SIZE_METERS as i32,
-                    ]),
-                    MeshGenerationPriority(priority),
-                ))
-                .id()
+    // the terrain of the chunk if it is still in memory, and the file it may be on disk in
+    fn look_up(&mut self, chunk_pos: (i32, i32), resolution: usize) -> TerrainSource {
+        // keyed by the resolution generated, so a cup chunk is only stored once
+        let resolution = ChunkTerrain::resolution_for(
+            self.generator.as_ref(),
+            world_offset(chunk_pos),
+            resolution,
+        );
+        let key = ChunkKey {
+            course: self.course.clone(),
+            chunk_pos,
+            resolution,
+        };
+        TerrainSource {
+            cached: self.cache.get(&key),
+            file: self.cache.file(&key),
+            key,
+        }
+    }
+
+    // The collider is only built with the first generation of a chunk. It always has the same
+    // resolution, a new level of detail keeps it.
+    fn spawn_generation(
+        &mut self,
+        chunk_pos: (i32, i32),
+        resolution: usize,
+        with_collider: bool,
+    ) -> PendingChunk {
+        let terrain = self.look_up(chunk_pos, resolution);
+        let ground = with_collider.then(|| self.look_up(chunk_pos, COLLIDER_RESOLUTION));
+        let generator = self.generator.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let generator = generator.as_ref();
+            let world_offset = world_offset(chunk_pos);
+            let key = terrain.key.clone();
+            let terrain = terrain.load(generator);
+            let chunk = Chunk::from_terrain(generator, world_offset, &terrain);
+            let mut terrains = vec![(key.clone(), terrain.clone())];
+            let collider = ground.map(|ground| {
+                let ground = if ground.key == key {
+                    terrain
+                } else {
+                    let ground_key = ground.key.clone();
+                    let ground = ground.load(generator);
+                    terrains.push((ground_key, ground.clone()));
+                    ground
+                };
+                ground.collider(generator, world_offset)
+            });
+            GeneratedChunk {
+                chunk,
+                collider,
+                terrains,
+            }
         });
+        PendingChunk(chunk_pos, task)
+    }
+
+    // loads the chunk, or generates it again if it should have another resolution at `distance`
+    fn load_chunk(&mut self, commands: &mut Commands, chunk_pos: (i32, i32), distance: f32) {
+        match self.chunks.get(&chunk_pos).copied() {
+            None => {
+                let resolution = resolution_for(distance, None);
+                let pending = self.spawn_generation(chunk_pos, resolution, true);
+                let entity = commands
+                    .spawn((pending, MeshGenerationPriority(distance)))
+                    .id();
+                self.chunks.insert(chunk_pos, entity);
+                self.pending.insert(chunk_pos);
+                self.resolutions.insert(chunk_pos, resolution);
+            }
+            Some(entity) => {
+                if self.pending.contains(&chunk_pos) {
+                    return;
+                }
+                let current = self.resolutions.get(&chunk_pos).copied();
+                let resolution = resolution_for(distance, current);
+                if current != Some(resolution) {
+                    // replaces a generation at another resolution still running
+                    let pending = self.spawn_generation(chunk_pos, resolution, false);
+                    commands.entity(entity).insert(pending);
+                    self.resolutions.insert(chunk_pos, resolution);
+                }
+            }
+        }
     }
 
     fn unload_chunk(&mut self, commands: &mut Commands, chunk_pos: (i32, i32)) {
-        if let Some(chunk) = self.chunks.remove(&chunk_pos) {
+        let Some(chunk) = self.chunks.remove(&chunk_pos) else {
+            return;
+        };
+        self.resolutions.remove(&chunk_pos);
+        self.colliders.remove(&chunk_pos);
+
+        if self.pending.remove(&chunk_pos) {
+            // dropping the task cancels the generation, a forced collider goes with the entity
+            commands
+                .entity(chunk)
+                .insert(ToUnload)
+                .remove::<PendingChunk>();
+        } else {
             commands
                 .entity(chunk)
-                .insert((ToUnload, LiftDownAnimation::new(0.0, 0.25)));
+                .insert((ToUnload, LiftDownAnimation::new(0.0, 0.25)))
+                .remove::<PendingChunk>();
         }
     }
 }
 
+// A chunk generated on the `AsyncComputeTaskPool`, replaced by its `Chunk` and collider once
+// done.
+#[derive(Component)]
+pub(crate) struct PendingChunk((i32, i32), Task<GeneratedChunk>);
+
+pub(crate) struct GeneratedChunk {
+    chunk: Chunk,
+    // only with the first generation of the chunk
+    collider: Option<Collider>,
+    // every terrain looked up, for the cache
+    terrains: Vec<(ChunkKey, Arc<ChunkTerrain>)>,
+}
+
+struct TerrainSource {
+    key: ChunkKey,
+    cached: Option<Arc<ChunkTerrain>>,
+    file: Option<PathBuf>,
+}
+
+impl TerrainSource {
+    // The terrain is taken from memory if it is still there, else from the file, else it is
+    // generated and written to the file.
+    fn load(self, generator: &dyn TerrainGenerator) -> Arc<ChunkTerrain> {
+        let world_offset = world_offset(self.key.chunk_pos);
+        let file = self.file;
+        self.cached.unwrap_or_else(|| {
+            let terrain = file.as_deref().and_then(cache::load).unwrap_or_else(|| {
+                let terrain = ChunkTerrain::generate(generator, world_offset, self.key.resolution);
+                if let Some(file) = &file {
+                    cache::store(file, &terrain);
+                }
+                terrain
+            });
+            Arc::new(terrain)
+        })
+    }
+}
+
+fn world_offset(chunk_pos: (i32, i32)) -> [i32; 2] {
+    [
+        chunk_pos.0 * CHUNK_SIZE_METERS as i32,
+        chunk_pos.1 * CHUNK_SIZE_METERS as i32,
+    ]
+}
+
 fn distance(from: (f32, f32), to: (f32, f32)) -> f32 {
     let dx = to.0 - from.0;
     let dz = to.1 - from.1;
@@ -115,8 +328,53 @@ pub(super) fn load_chunks(
         }
     }
 
-    for (chunk_pos, priority) in schedule_load {
-        chunks.load_chunk(&mut commands, chunk_pos, priority);
+    // the tasks are started closest first, so nearby chunks are done first
+    let mut schedule_load = schedule_load.into_iter().collect::<Vec<_>>();
+    schedule_load.sort_by(|a, b| a.1.total_cmp(&b.1));
+    for (chunk_pos, distance) in schedule_load {
+        chunks.load_chunk(&mut commands, chunk_pos, distance);
+    }
+}
+
+pub(crate) fn receive_generated_chunks(
+    query: Query<(Entity, &mut PendingChunk)>,
+    shown: Query<&Chunk, With<Mesh3d>>,
+    mut chunks: ResMut<ChunkManager>,
+    mut commands: Commands,
+) {
+    for (entity, mut pending) in query {
+        let Some(generated) = block_on(poll_once(&mut pending.1)) else {
+            continue;
+        };
+        let chunk_pos = pending.0;
+        // the chunk was unloaded or the course replaced since, the entity is being despawned
+        if chunks.chunks.get(&chunk_pos) != Some(&entity) {
+            continue;
+        }
+        chunks.pending.remove(&chunk_pos);
+        for (key, terrain) in generated.terrains {
+            chunks.cache.insert(key, terrain);
+        }
+
+        let chunk = generated.chunk;
+        let previous = shown.get(entity).ok().map(Chunk::surface);
+        // the collider goes in right away, before the chunk is meshed, unless it was forced
+        let collider = generated
+            .collider
+            .filter(|_| chunks.colliders.insert(chunk_pos))
+            .map(|collider| {
+                spawn_chunk_collider(&mut commands, collider, chunk.world_offset, chunk.cup())
+            });
+        let mut entity = commands.entity(entity);
+        entity.try_remove::<PendingChunk>();
+        if let Some(collider) = collider {
+            entity.try_insert(HasCollider(collider));
+        }
+        entity.try_insert(chunk);
+        // a new resolution of a chunk already shown
+        if let Some(previous) = previous {
+            entity.try_insert(Remesh(previous));
+        }
     }
 }
 
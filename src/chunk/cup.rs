@@ -0,0 +1,248 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+
+use crate::generation::TerrainGenerator;
+
+// regulation cup, 108 mm across
+pub const CUP_RADIUS: f32 = 0.054;
+pub const CUP_DEPTH: f32 = 0.1;
+const CUP_SEGMENTS: usize = 32;
+// cells around the cell of the hole that are replaced by the rim mesh
+const CUTOUT_CELLS: i32 = 1;
+
+// The grid cells (world metres, inclusive min, exclusive max) cut out of the terrain around
+// the hole. The rim mesh closes the gap between the cut and the cup.
+pub fn cup_cutout(hole: [f32; 2]) -> (IVec2, IVec2) {
+    let cell = IVec2::new(hole[0].floor() as i32, hole[1].floor() as i32);
+    (
+        cell - IVec2::splat(CUTOUT_CELLS),
+        cell + IVec2::splat(CUTOUT_CELLS + 1),
+    )
+}
+
+// whether the cutout reaches into the square of `size` metres at `world_offset`
+pub fn cutout_overlaps_square(hole: [f32; 2], world_offset: [i32; 2], size: i32) -> bool {
+    let (min, max) = cup_cutout(hole);
+    min.x < world_offset[0] + size
+        && world_offset[0] < max.x
+        && min.y < world_offset[1] + size
+        && world_offset[1] < max.y
+}
+
+pub fn in_cup_cutout(hole: [f32; 2], cell: IVec2) -> bool {
+    let (min, max) = cup_cutout(hole);
+    cell.cmpge(min).all() && cell.cmplt(max).all()
+}
+
+// Geometry of the cup in world space: the ring of ground between the cutout edge and the cup,
+// and the wall and bottom of the cup itself.
+pub struct CupGeometry {
+    pub bottom: Vec3,
+    pub rim_positions: Vec<[f32; 3]>,
+    pub rim_indices: Vec<u32>,
+    pub cup_positions: Vec<[f32; 3]>,
+    pub cup_indices: Vec<u32>,
+}
+
+impl CupGeometry {
+    pub fn generate(generator: &dyn TerrainGenerator) -> Self {
+        let [hole_x, hole_z] = generator.hole();
+        let center = Vec2::new(hole_x, hole_z);
+        let (min, max) = cup_cutout(generator.hole());
+        let (min, max) = (min.as_vec2(), max.as_vec2());
+
+        // heights along the cutout edge follow the terrain mesh, which is linear between
+        // grid points
+        let edge_height = |p: Vec2| {
+            let (x0, z0) = (p.x.floor(), p.y.floor());
+            let (x1, z1) = (p.x.ceil(), p.y.ceil());
+            let t = (p.x - x0).max(p.y - z0);
+            let a = generator.height_at(x0, z0);
+            let b = generator.height_at(x1, z1);
+            a + (b - a) * t
+        };
+        let angle_of = |p: Vec2| {
+            let angle = (p.y - center.y).atan2(p.x - center.x);
+            angle.rem_euclid(std::f32::consts::TAU)
+        };
+
+        // inner loop around the cup
+        let circle: Vec<(f32, Vec3)> = (0..CUP_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / CUP_SEGMENTS as f32 * std::f32::consts::TAU;
+                let p = center + Vec2::from_angle(angle) * CUP_RADIUS;
+                (angle, Vec3::new(p.x, generator.height_at(p.x, p.y), p.y))
+            })
+            .collect();
+
+        // outer loop along the cutout edge: every grid point plus where the rays through the
+        // circle points leave the cutout
+        let mut outer: Vec<(f32, Vec3)> = Vec::new();
+        for x in min.x as i32..=max.x as i32 {
+            for z in min.y as i32..=max.y as i32 {
+                let p = Vec2::new(x as f32, z as f32);
+                if p.x == min.x || p.x == max.x || p.y == min.y || p.y == max.y {
+                    outer.push((angle_of(p), Vec3::new(p.x, edge_height(p), p.y)));
+                }
+            }
+        }
+        for (angle, _) in &circle {
+            let direction = Vec2::from_angle(*angle);
+            let to_x = if direction.x > 0.0 { max.x } else { min.x } - center.x;
+            let to_z = if direction.y > 0.0 { max.y } else { min.y } - center.y;
+            let t = (to_x / direction.x).abs().min((to_z / direction.y).abs());
+            let p = center + direction * t;
+            outer.push((*angle, Vec3::new(p.x, edge_height(p), p.y)));
+        }
+        outer.sort_by(|a, b| a.0.total_cmp(&b.0));
+        outer.dedup_by(|a, b| a.1.xz().distance(b.1.xz()) < 1e-4);
+
+        // zip the two loops together, always advancing the one whose next point comes first
+        let mut rim_positions: Vec<[f32; 3]> = circle.iter().map(|(_, p)| p.to_array()).collect();
+        rim_positions.extend(outer.iter().map(|(_, p)| p.to_array()));
+        let mut rim_indices = Vec::new();
+        let (n, m) = (circle.len(), outer.len());
+        let inner_angle = |i: usize| {
+            if i == n {
+                std::f32::consts::TAU
+            } else {
+                circle[i].0
+            }
+        };
+        let outer_angle = |j: usize| {
+            if j == m {
+                std::f32::consts::TAU
+            } else {
+                outer[j].0
+            }
+        };
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            let inner = (i % n) as u32;
+            let outer_index = (n + j % m) as u32;
+            if j == m || (i < n && inner_angle(i + 1) <= outer_angle(j + 1)) {
+                push_triangle(
+                    &rim_positions,
+                    &mut rim_indices,
+                    [inner, ((i + 1) % n) as u32, outer_index],
+                    Vec3::Y,
+                );
+                i += 1;
+            } else {
+                push_triangle(
+                    &rim_positions,
+                    &mut rim_indices,
+                    [inner, (n + (j + 1) % m) as u32, outer_index],
+                    Vec3::Y,
+                );
+                j += 1;
+            }
+        }
+
+        // wall and flat bottom
+        let lowest_rim = circle
+            .iter()
+            .map(|(_, p)| p.y)
+            .fold(f32::INFINITY, f32::min);
+        let bottom = lowest_rim - CUP_DEPTH;
+        let mut cup_positions: Vec<[f32; 3]> = circle.iter().map(|(_, p)| p.to_array()).collect();
+        cup_positions.extend(circle.iter().map(|(_, p)| [p.x, bottom, p.z]));
+        cup_positions.push([center.x, bottom, center.y]);
+        let mut cup_indices = Vec::new();
+        for (i, (_, rim)) in circle.iter().enumerate() {
+            let next = (i + 1) % n;
+            let (top, top_next) = (i as u32, next as u32);
+            let (low, low_next) = ((n + i) as u32, (n + next) as u32);
+            let inward = -Vec3::new(rim.x - center.x, 0.0, rim.z - center.y);
+            push_triangle(
+                &cup_positions,
+                &mut cup_indices,
+                [top, low, top_next],
+                inward,
+            );
+            push_triangle(
+                &cup_positions,
+                &mut cup_indices,
+                [top_next, low, low_next],
+                inward,
+            );
+            push_triangle(
+                &cup_positions,
+                &mut cup_indices,
+                [low, (2 * n) as u32, low_next],
+                Vec3::Y,
+            );
+        }
+
+        CupGeometry {
+            bottom: Vec3::new(center.x, bottom, center.y),
+            rim_positions,
+            rim_indices,
+            cup_positions,
+            cup_indices,
+        }
+    }
+
+    pub fn rim_mesh(&self) -> Mesh {
+        build_mesh(&self.rim_positions, &self.rim_indices)
+    }
+
+    pub fn cup_mesh(&self) -> Mesh {
+        build_mesh(&self.cup_positions, &self.cup_indices)
+    }
+
+    // rim and cup as one triangle soup for the collider
+    pub fn collider_geometry(&self) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+        let offset = self.rim_positions.len() as u32;
+        let vertices = self
+            .rim_positions
+            .iter()
+            .chain(&self.cup_positions)
+            .map(|p| Vec3::from_array(*p))
+            .collect();
+        let indices = self
+            .rim_indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .chain(
+                self.cup_indices
+                    .chunks_exact(3)
+                    .map(|t| [t[0] + offset, t[1] + offset, t[2] + offset]),
+            )
+            .collect();
+        (vertices, indices)
+    }
+}
+
+// adds the triangle with its front face pointing along `facing`
+fn push_triangle(
+    positions: &[[f32; 3]],
+    indices: &mut Vec<u32>,
+    [a, b, c]: [u32; 3],
+    facing: Vec3,
+) {
+    let pa = Vec3::from_array(positions[a as usize]);
+    let pb = Vec3::from_array(positions[b as usize]);
+    let pc = Vec3::from_array(positions[c as usize]);
+    if (pb - pa).cross(pc - pa).dot(facing) >= 0.0 {
+        indices.extend([a, b, c]);
+    } else {
+        indices.extend([a, c, b]);
+    }
+}
+
+fn build_mesh(positions: &[[f32; 3]], indices: &[u32]) -> Mesh {
+    let mut result = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    result.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions.to_vec());
+    result.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        positions.iter().map(|p| [p[0], p[2]]).collect::<Vec<_>>(),
+    );
+    result.insert_indices(Indices::U32(indices.to_vec()));
+    result.compute_normals();
+    result
+}
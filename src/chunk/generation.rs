@@ -1,8 +1,14 @@
 use crate::animation::LiftUpAnimation;
+use crate::chunk::cache::ChunkTerrain;
 use crate::chunk::chunk_manager::MeshGenerationPriority;
-use crate::chunk::{CHUNK_FIDELITY, CHUNK_SIZE_METERS, Chunk};
-use crate::generation::{Prop, TerrainGenerator};
-use crate::material::ground::GroundMaterial;
+use crate::chunk::cup::{CupGeometry, cutout_overlaps_square, in_cup_cutout};
+use crate::chunk::lod::{COLLIDER_RESOLUTION, CUP_RESOLUTION};
+use crate::chunk::{CHUNK_SIZE_METERS, Chunk};
+use crate::generation::{Prop, PropType, TerrainGenerator};
+use crate::material::ground::{GroundMaterial, GroundStyle, hazard_buffer};
+use avian3d::parry::na::{DMatrix, Vector3};
+use avian3d::parry::shape::{HeightField, HeightFieldCellStatus, SharedShape};
+use avian3d::prelude::Collider;
 use bevy::asset::{Assets, Handle, RenderAssetUsages};
 use bevy::ecs::component::Component;
 use bevy::gltf::{GltfAssetLabel, GltfMaterialName};
@@ -15,94 +21,236 @@ use bevy::mesh::{Indices, Mesh, Mesh3d, Meshable, PrimitiveTopology};
 use bevy::pbr::{ExtendedMaterial, MaterialExtension, MeshMaterial3d, StandardMaterial};
 use bevy::prelude::*;
 use bevy::render::render_resource::AsBindGroup;
+use bevy::render::storage::ShaderStorageBuffer;
 use bevy::scene::SceneInstanceReady;
 use bevy::shader::ShaderRef;
 
 const CHUNKS_MESHED_PER_TICK: usize = 24;
 const WATER_HEIGHT: f32 = -5.0;
+// how far the skirts hang below the edge of a chunk, per metre of grid cell, deep enough to
+// cover the cracks to a neighbour of another resolution
+const SKIRT_DEPTH: f32 = 1.5;
+// seconds a chunk takes to morph into the surface of its new resolution
+const MORPH_DURATION: f32 = 0.5;
 
-impl Chunk {
-    pub fn generate_at(generator: &dyn TerrainGenerator, world_offset: [i32; 2]) -> Self {
-        let mut elevation = Box::new([[0.0; CHUNK_FIDELITY + 1]; CHUNK_FIDELITY + 1]);
-
-        for x in 0..=CHUNK_FIDELITY {
-            for z in 0..=CHUNK_FIDELITY {
-                let height = generator.height_at(
-                    x as f32 + world_offset[0] as f32,
-                    z as f32 + world_offset[1] as f32,
-                );
-                elevation[x][z] = height
+impl ChunkTerrain {
+    // The resolution a chunk asked for at `resolution` is generated at. The chunks around the
+    // cup always use `CUP_RESOLUTION`, as the cup is cut into that grid.
+    pub fn resolution_for(
+        generator: &dyn TerrainGenerator,
+        world_offset: [i32; 2],
+        resolution: usize,
+    ) -> usize {
+        if cutout_overlaps_square(generator.hole(), world_offset, CHUNK_SIZE_METERS as i32) {
+            CUP_RESOLUTION
+        } else {
+            resolution
+        }
+    }
+
+    // `resolution` as given by `resolution_for`
+    pub fn generate(
+        generator: &dyn TerrainGenerator,
+        world_offset: [i32; 2],
+        resolution: usize,
+    ) -> Self {
+        let size = CHUNK_SIZE_METERS as f32;
+        let step = size / resolution as f32;
+
+        let mut elevation = Vec::with_capacity((resolution + 1) * (resolution + 1));
+        let mut normals = Vec::with_capacity((resolution + 1) * (resolution + 1));
+        for x in 0..=resolution {
+            for z in 0..=resolution {
+                let world_x = x as f32 * step + world_offset[0] as f32;
+                let world_z = z as f32 * step + world_offset[1] as f32;
+                elevation.push(generator.height_at(world_x, world_z));
+                // from the generator instead of the triangles of this chunk alone, and with
+                // the same spacing at every level, so the normals on the border match those of
+                // the neighbour
+                normals.push(generator.normal_at(world_x, world_z).to_array());
             }
         }
 
-        let props = generator.props_in_chunk((world_offset[0], world_offset[1]));
-        let bunker = generator.nearest_bunker(world_offset);
+        ChunkTerrain {
+            resolution,
+            elevation,
+            normals,
+            props: generator.props_in_chunk((world_offset[0], world_offset[1])),
+            hazards: generator.hazards_in_chunk(world_offset),
+        }
+    }
+
+    // Collider over the grid of the terrain, which is looked up at `COLLIDER_RESOLUTION`.
+    pub fn collider(&self, generator: &dyn TerrainGenerator, world_offset: [i32; 2]) -> Collider {
+        let resolution = self.resolution;
+        heightfield(generator.hole(), world_offset, resolution, |x, z| {
+            self.elevation[x * (resolution + 1) + z]
+        })
+    }
 
+    // The same collider straight from the generator, without the normals, props and hazards
+    // of the terrain.
+    pub fn sample_collider(generator: &dyn TerrainGenerator, world_offset: [i32; 2]) -> Collider {
+        heightfield(
+            generator.hole(),
+            world_offset,
+            COLLIDER_RESOLUTION,
+            |x, z| {
+                generator.height_at(
+                    (world_offset[0] + x as i32) as f32,
+                    (world_offset[1] + z as i32) as f32,
+                )
+            },
+        )
+    }
+}
+
+// Heightfield over `resolution` cells per side, centred on the chunk. The mesh at the same
+// resolution splits every cell along the same diagonal. The cells replaced by the rim of the
+// cup are left out, which takes cells of one metre.
+fn heightfield(
+    hole: [f32; 2],
+    world_offset: [i32; 2],
+    resolution: usize,
+    height: impl Fn(usize, usize) -> f32,
+) -> Collider {
+    let size = CHUNK_SIZE_METERS as f32;
+    // rows run along z, columns along x
+    let heights = DMatrix::from_fn(resolution + 1, resolution + 1, |z, x| height(x, z));
+    let mut heightfield = HeightField::new(heights, Vector3::new(size, 1.0, size));
+
+    if cutout_overlaps_square(hole, world_offset, CHUNK_SIZE_METERS as i32) {
+        for x in 0..resolution {
+            for z in 0..resolution {
+                let cell = IVec2::new(world_offset[0] + x as i32, world_offset[1] + z as i32);
+                if in_cup_cutout(hole, cell) {
+                    heightfield.set_cell_status(z, x, HeightFieldCellStatus::CELL_REMOVED);
+                }
+            }
+        }
+    }
+
+    Collider::from(SharedShape::new(heightfield))
+}
+
+// The cup of the course, on the chunk at `world_offset` only if the hole is on it.
+pub(super) fn cup_in_chunk(
+    generator: &dyn TerrainGenerator,
+    world_offset: [i32; 2],
+) -> Option<CupGeometry> {
+    let size = CHUNK_SIZE_METERS as f32;
+    let hole = generator.hole();
+    let owns_hole = (0..2).all(|axis| {
+        let offset = world_offset[axis] as f32;
+        offset <= hole[axis] && hole[axis] < offset + size
+    });
+    owns_hole.then(|| CupGeometry::generate(generator))
+}
+
+impl Chunk {
+    // everything but the terrain comes from the generator, it is cheap to compute
+    pub fn from_terrain(
+        generator: &dyn TerrainGenerator,
+        world_offset: [i32; 2],
+        terrain: &ChunkTerrain,
+    ) -> Self {
         Chunk {
             world_offset,
-            elevation,
-            props,
-            course: generator.course_layout(),
-            bunker,
+            resolution: terrain.resolution,
+            elevation: terrain.elevation.clone(),
+            normals: terrain.normals.clone(),
+            props: terrain.props.clone(),
+            course: generator.course_layout().uniform(),
+            ground_style: GroundStyle::of(generator),
+            hazards: terrain.hazards.clone(),
+            hole: generator.hole(),
+            cup: cup_in_chunk(generator, world_offset),
         }
     }
 
     fn generate_mesh(&self) -> Mesh {
-        let mut result = Mesh::new(
-            PrimitiveTopology::TriangleList,
-            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-        );
-
-        result.insert_attribute(
-            Mesh::ATTRIBUTE_POSITION,
-            self.elevation
-                .iter()
-                .enumerate()
-                .flat_map(|(x, row)| {
-                    row.iter()
-                        .enumerate()
-                        .map(move |(z, &height)| [x as f32, height, z as f32])
-                })
-                .collect::<Vec<_>>(),
-        );
-
-        result.insert_attribute(
-            Mesh::ATTRIBUTE_UV_0,
-            self.elevation
-                .iter()
-                .enumerate()
-                .flat_map(|(x, row)| {
-                    row.iter().enumerate().map(move |(z, _)| {
-                        [
-                            x as f32 / CHUNK_SIZE_METERS as f32,
-                            z as f32 / CHUNK_SIZE_METERS as f32,
-                        ]
-                    })
-                })
-                .collect::<Vec<_>>(),
-        );
-
-        const CHUNK_FIDELITY_U32: u32 = CHUNK_FIDELITY as u32;
-
-        result.insert_indices(Indices::U32(
-            (0..CHUNK_FIDELITY_U32)
-                .flat_map(|x| (0..CHUNK_FIDELITY_U32).map(move |y| (x, y)))
-                .flat_map(move |(x, y)| {
-                    [
-                        y * (CHUNK_FIDELITY_U32 + 1) + x,
-                        y * (CHUNK_FIDELITY_U32 + 1) + x + 1,
-                        y * (CHUNK_FIDELITY_U32 + 1) + x + CHUNK_FIDELITY_U32 + 1,
-                        y * (CHUNK_FIDELITY_U32 + 1) + x + 1,
-                        y * (CHUNK_FIDELITY_U32 + 1) + x + CHUNK_FIDELITY_U32 + 2,
-                        y * (CHUNK_FIDELITY_U32 + 1) + x + CHUNK_FIDELITY_U32 + 1,
-                    ]
-                })
+        // only chunks around the hole use the cutout, always at one cell per metre
+        let cutout = self.overlaps_cup_cutout();
+        grid_mesh(
+            self.world_offset,
+            self.resolution,
+            |x, z| (self.grid_height(x, z), self.grid_normal(x, z)),
+            // the rim mesh of the cup replaces the cells around the hole
+            |x, z| {
+                let cell = IVec2::new(
+                    self.world_offset[0] + x as i32,
+                    self.world_offset[1] + z as i32,
+                );
+                !(cutout && in_cup_cutout(self.hole, cell))
+            },
+        )
+    }
+
+    // the grid as it is now, for the next resolution to morph from
+    pub(super) fn surface(&self) -> GridSurface {
+        GridSurface {
+            resolution: self.resolution,
+            elevation: self.elevation.clone(),
+            normals: self.normals.clone(),
+        }
+    }
+
+    // only on the chunk containing the hole
+    pub fn cup(&self) -> Option<&CupGeometry> {
+        self.cup.as_ref()
+    }
+
+    // Walls hanging down from the four edges of the chunk. Where a neighbour has another
+    // resolution its edge doesn't line up with this one, the skirts fill the gap between them.
+    // Kept out of the terrain mesh, so they never end up in the collider.
+    fn generate_skirt_mesh(&self) -> Mesh {
+        let resolution = self.resolution;
+        let step = CHUNK_SIZE_METERS as f32 / resolution as f32;
+        let depth = SKIRT_DEPTH * step;
+
+        let edges: [Vec<(usize, usize)>; 4] = [
+            (0..=resolution).map(|i| (i, 0)).collect(),
+            (0..=resolution).map(|i| (resolution, i)).collect(),
+            (0..=resolution)
+                .map(|i| (resolution - i, resolution))
                 .collect(),
-        ));
+            (0..=resolution).map(|i| (0, resolution - i)).collect(),
+        ];
 
-        result.compute_normals();
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        for edge in edges {
+            let first = positions.len() as u32;
+            for (x, z) in edge {
+                let (px, pz) = (x as f32 * step, z as f32 * step);
+                let height = self.grid_height(x, z);
+                let uv = [x as f32 / resolution as f32, z as f32 / resolution as f32];
+                positions.push([px, height, pz]);
+                positions.push([px, height - depth, pz]);
+                // lit like the ground above them
+                normals.extend([self.grid_normal(x, z); 2]);
+                uvs.extend([uv, uv]);
+            }
+            for i in 0..resolution as u32 {
+                let (top, bottom) = (first + i * 2, first + i * 2 + 1);
+                let (next_top, next_bottom) = (top + 2, bottom + 2);
+                // both sides, the skirt is seen from inside or outside depending on the slope
+                indices.extend([top, bottom, next_top, next_top, bottom, next_bottom]);
+                indices.extend([top, next_top, bottom, next_top, next_bottom, bottom]);
+            }
+        }
 
-        result.translated_by(Vec3::new(
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+        .translated_by(Vec3::new(
             self.world_offset[0] as f32,
             0.0,
             self.world_offset[1] as f32,
@@ -110,6 +258,170 @@ impl Chunk {
     }
 }
 
+// Terrain mesh over a grid of `resolution` cells per side, `vertex` gives the height and normal
+// of every grid point. Cells for which `keep_cell` is false are left out.
+fn grid_mesh(
+    world_offset: [i32; 2],
+    resolution: usize,
+    vertex: impl Fn(usize, usize) -> (f32, [f32; 3]),
+    keep_cell: impl Fn(usize, usize) -> bool,
+) -> Mesh {
+    let mut result = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    let step = CHUNK_SIZE_METERS as f32 / resolution as f32;
+    let grid = || (0..=resolution).flat_map(|x| (0..=resolution).map(move |z| (x, z)));
+
+    result.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        grid()
+            .map(|(x, z)| [x as f32 * step, vertex(x, z).0, z as f32 * step])
+            .collect::<Vec<_>>(),
+    );
+
+    result.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        grid().map(|(x, z)| vertex(x, z).1).collect::<Vec<_>>(),
+    );
+
+    result.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        grid()
+            .map(|(x, z)| [x as f32 / resolution as f32, z as f32 / resolution as f32])
+            .collect::<Vec<_>>(),
+    );
+
+    let resolution_u32 = resolution as u32;
+    result.insert_indices(Indices::U32(
+        (0..resolution_u32)
+            .flat_map(|x| (0..resolution_u32).map(move |y| (x, y)))
+            .filter(|(x, y)| keep_cell(*y as usize, *x as usize))
+            .flat_map(move |(x, y)| {
+                [
+                    y * (resolution_u32 + 1) + x,
+                    y * (resolution_u32 + 1) + x + 1,
+                    y * (resolution_u32 + 1) + x + resolution_u32 + 1,
+                    y * (resolution_u32 + 1) + x + 1,
+                    y * (resolution_u32 + 1) + x + resolution_u32 + 2,
+                    y * (resolution_u32 + 1) + x + resolution_u32 + 1,
+                ]
+            })
+            .collect(),
+    ));
+
+    result.translated_by(Vec3::new(
+        world_offset[0] as f32,
+        0.0,
+        world_offset[1] as f32,
+    ))
+}
+
+// Marks the skirts of a chunk, replaced together with its terrain mesh.
+#[derive(Component)]
+pub(super) struct ChunkSkirt;
+
+// Inserted when a chunk was generated again at another resolution, with the grid it had
+// before.
+#[derive(Component)]
+pub(super) struct Remesh(pub(super) GridSurface);
+
+// The heights and normals of a chunk at one resolution.
+pub(super) struct GridSurface {
+    resolution: usize,
+    // (resolution + 1)² points, row by row along x
+    elevation: Vec<f32>,
+    normals: Vec<[f32; 3]>,
+}
+
+impl GridSurface {
+    // height and normal at `x`, `z` metres into the chunk, on the triangles of its mesh
+    fn sample(&self, x: f32, z: f32) -> (f32, Vec3) {
+        triangle_corners(self.resolution, x, z).into_iter().fold(
+            (0.0, Vec3::ZERO),
+            |(height, normal), ((x, z), weight)| {
+                let i = x * (self.resolution + 1) + z;
+                (
+                    height + self.elevation[i] * weight,
+                    normal + Vec3::from(self.normals[i]) * weight,
+                )
+            },
+        )
+    }
+}
+
+// The three grid points around `x`, `z` metres into a chunk of `resolution` cells per side,
+// with their weights. Every cell is split from (x, z + 1) to (x + 1, z), like in `grid_mesh`
+// and the heightfield collider, so this is the surface that is drawn and played on.
+pub(super) fn triangle_corners(resolution: usize, x: f32, z: f32) -> [((usize, usize), f32); 3] {
+    let cells = resolution as f32 / CHUNK_SIZE_METERS as f32;
+    let (grid_x, grid_z) = (x * cells, z * cells);
+    // the last cell also covers the far edge
+    let x_idx = (grid_x.floor() as usize).min(resolution - 1);
+    let z_idx = (grid_z.floor() as usize).min(resolution - 1);
+    let (x_sub, z_sub) = (grid_x - x_idx as f32, grid_z - z_idx as f32);
+
+    if x_sub + z_sub <= 1.0 {
+        [
+            ((x_idx, z_idx), 1.0 - x_sub - z_sub),
+            ((x_idx + 1, z_idx), x_sub),
+            ((x_idx, z_idx + 1), z_sub),
+        ]
+    } else {
+        [
+            ((x_idx + 1, z_idx + 1), x_sub + z_sub - 1.0),
+            ((x_idx, z_idx + 1), 1.0 - x_sub),
+            ((x_idx + 1, z_idx), 1.0 - z_sub),
+        ]
+    }
+}
+
+// A chunk blending from the surface of its old resolution into the new one, on a grid as fine
+// as the finer of both. Once done, the mesh of the new resolution takes over.
+#[derive(Component)]
+pub(super) struct Morph {
+    resolution: usize,
+    // height and normal of every grid point on the old and the new surface
+    from: Vec<(f32, Vec3)>,
+    to: Vec<(f32, Vec3)>,
+    elapsed: f32,
+}
+
+impl Morph {
+    fn new(from: &GridSurface, to: &GridSurface) -> Self {
+        let resolution = from.resolution.max(to.resolution);
+        let step = CHUNK_SIZE_METERS as f32 / resolution as f32;
+        let sample = |surface: &GridSurface| {
+            (0..=resolution)
+                .flat_map(|x| (0..=resolution).map(move |z| (x, z)))
+                .map(|(x, z)| surface.sample(x as f32 * step, z as f32 * step))
+                .collect()
+        };
+        Morph {
+            resolution,
+            from: sample(from),
+            to: sample(to),
+            elapsed: 0.0,
+        }
+    }
+
+    fn blend(&self, i: usize, t: f32) -> (f32, [f32; 3]) {
+        let ((from_height, from_normal), (to_height, to_normal)) = (self.from[i], self.to[i]);
+        let normal = from_normal.lerp(to_normal, t).normalize_or(Vec3::Y);
+        (from_height.lerp(to_height, t), normal.to_array())
+    }
+
+    fn mesh(&self, world_offset: [i32; 2], t: f32) -> Mesh {
+        grid_mesh(
+            world_offset,
+            self.resolution,
+            |x, z| self.blend(x * (self.resolution + 1) + z, t),
+            |_, _| true,
+        )
+    }
+}
+
 const SHADER_ASSET_PATH: &str = "shaders/water.wgsl";
 
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
@@ -133,6 +445,8 @@ pub(super) fn insert_chunk_mesh(
     asset_server: Res<AssetServer>,
     mut ground_materials: ResMut<Assets<ExtendedMaterial<StandardMaterial, GroundMaterial>>>,
     mut water_material: ResMut<Assets<ExtendedMaterial<StandardMaterial, WaterExtension>>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>,
     mut commands: Commands,
 ) {
     let selection = query
@@ -155,7 +469,11 @@ pub(super) fn insert_chunk_mesh(
                 //base_color: Color::srgba(1.0, 1.0, 1.0, 0.0),
                 ..default()
             },
-            extension: GroundMaterial::new(chunk.course.clone(), chunk.bunker.clone()),
+            extension: GroundMaterial::new(
+                chunk.course.clone(),
+                chunk.ground_style,
+                storage_buffers.add(hazard_buffer(&chunk.hazards)),
+            ),
         });
         let normal_handle =
             asset_server.load_with_settings("textures/water/water0342normal.png", |s: &mut _| {
@@ -174,7 +492,7 @@ pub(super) fn insert_chunk_mesh(
             .entity(entity)
             .insert((
                 Mesh3d(terrain_mesh_handle),
-                MeshMaterial3d(material),
+                MeshMaterial3d(material.clone()),
                 NotShadowCaster,
                 // FadeInAnimation::new(0.25),
                 Transform::from_xyz(0.0, -100.0, 0.0),
@@ -182,12 +500,19 @@ pub(super) fn insert_chunk_mesh(
             ))
             .remove::<MeshGenerationPriority>();
 
+        // skirts hiding the cracks to neighbours of another resolution
+        let skirt = commands
+            .spawn((
+                ChunkSkirt,
+                Mesh3d(meshes.add(chunk.generate_skirt_mesh())),
+                MeshMaterial3d(material.clone()),
+                NotShadowCaster,
+            ))
+            .id();
+        commands.entity(entity).add_child(skirt);
+
         // water plane mesh
-        if chunk
-            .elevation
-            .iter()
-            .any(|row| row.iter().any(|height| *height < WATER_HEIGHT))
-        {
+        if chunk.elevation.iter().any(|height| *height < WATER_HEIGHT) {
             let x = chunk.world_offset[0] as f32 + CHUNK_SIZE_METERS as f32 * 0.5;
             let z = chunk.world_offset[1] as f32 + CHUNK_SIZE_METERS as f32 * 0.5;
 
@@ -222,34 +547,164 @@ pub(super) fn insert_chunk_mesh(
             commands.entity(entity).add_child(child);
         }
 
+        // cup with its rim, the colliders went in with the heightfield
+        if let Some(cup) = &chunk.cup {
+            let child = commands
+                .spawn((
+                    Transform::default(),
+                    Mesh3d(meshes.add(cup.rim_mesh())),
+                    MeshMaterial3d(material),
+                    NotShadowCaster,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        Mesh3d(meshes.add(cup.cup_mesh())),
+                        MeshMaterial3d(standard_materials.add(StandardMaterial {
+                            base_color: Color::srgb(0.05, 0.04, 0.03),
+                            perceptual_roughness: 1.0,
+                            ..default()
+                        })),
+                    ));
+                })
+                .id();
+
+            commands.entity(entity).add_child(child);
+        }
+
         // props
+        let scrub = chunk
+            .props
+            .iter()
+            .any(|prop| matches!(prop.prop_type, PropType::Scrub))
+            .then(|| {
+                (
+                    meshes.add(Sphere::new(1.0).mesh().ico(1).unwrap()),
+                    standard_materials.add(StandardMaterial {
+                        base_color: Color::srgb(0.45, 0.45, 0.25),
+                        perceptual_roughness: 0.9,
+                        ..default()
+                    }),
+                )
+            });
         for Prop {
+            prop_type,
             position: (px, py, pz),
             seed,
-            ..
         } in &chunk.props
         {
-            let height = 0.035 + ((*seed) % 100) as f32 * 0.0001;
-            let child = commands
-                .spawn((
-                    Transform::from_xyz(
-                        chunk.world_offset[0] as f32 + *px,
-                        *py - 0.5,
-                        chunk.world_offset[1] as f32 + *pz,
-                    )
-                    .with_scale(Vec3::splat(height)),
-                    SceneRoot(
-                        asset_server
-                            .load(GltfAssetLabel::Scene(0).from_asset("model/pine_tree.glb")),
-                    ),
-                ))
-                .id();
+            let translation = Vec3::new(
+                chunk.world_offset[0] as f32 + *px,
+                *py,
+                chunk.world_offset[1] as f32 + *pz,
+            );
+            let child =
+                match (prop_type, &scrub) {
+                    (PropType::Scrub, Some((mesh, material))) => {
+                        // low, flattened bush half sunk into the ground
+                        let size = 0.6 + ((*seed) % 100) as f32 * 0.006;
+                        commands
+                            .spawn((
+                                Transform::from_translation(translation)
+                                    .with_rotation(Quat::from_rotation_y(*seed as f32))
+                                    .with_scale(Vec3::new(size, size * 0.5, size * 0.8)),
+                                Mesh3d(mesh.clone()),
+                                MeshMaterial3d(material.clone()),
+                            ))
+                            .id()
+                    }
+                    _ => {
+                        let height = 0.035 + ((*seed) % 100) as f32 * 0.0001;
+                        commands
+                            .spawn((
+                                Transform::from_translation(translation - Vec3::Y * 0.5)
+                                    .with_scale(Vec3::splat(height)),
+                                SceneRoot(asset_server.load(
+                                    GltfAssetLabel::Scene(0).from_asset("model/pine_tree.glb"),
+                                )),
+                            ))
+                            .id()
+                    }
+                };
 
             commands.entity(entity).add_child(child);
         }
     }
 }
 
+// Swaps the terrain and skirt meshes of a chunk for those of its new resolution. The old
+// meshes stay until the new chunk is done, so nothing disappears in between. Where the grid
+// changes, the terrain first morphs from the old surface into the new one, see `morph_chunks`.
+pub(super) fn remesh_chunks(
+    query: Query<(Entity, &Chunk, &mut Mesh3d, &Children, &Remesh)>,
+    mut skirts: Query<&mut Mesh3d, (With<ChunkSkirt>, Without<Chunk>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    for (entity, chunk, mut mesh, children, Remesh(from)) in query {
+        let mut entity = commands.entity(entity);
+        entity.remove::<Remesh>();
+
+        if from.resolution != chunk.resolution {
+            let morph = Morph::new(from, &chunk.surface());
+            mesh.0 = meshes.add(morph.mesh(chunk.world_offset, 0.0));
+            entity.insert(morph);
+            continue;
+        }
+
+        mesh.0 = meshes.add(chunk.generate_mesh());
+        let mut skirt_iter = skirts.iter_many_mut(children);
+        while let Some(mut skirt) = skirt_iter.fetch_next() {
+            skirt.0 = meshes.add(chunk.generate_skirt_mesh());
+        }
+        entity.remove::<Morph>();
+    }
+}
+
+// Moves the heights of morphing chunks towards their new surface, and puts in the meshes of
+// their new resolution once they got there.
+pub(super) fn morph_chunks(
+    query: Query<(Entity, &Chunk, &mut Mesh3d, &Children, &mut Morph)>,
+    mut skirts: Query<&mut Mesh3d, (With<ChunkSkirt>, Without<Chunk>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, chunk, mut mesh, children, mut morph) in query {
+        morph.elapsed += time.delta_secs();
+        let t = morph.elapsed / MORPH_DURATION;
+
+        if t < 1.0 {
+            if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                let vertices = (0..morph.from.len()).map(|i| morph.blend(i, t));
+                let (heights, normals): (Vec<_>, Vec<_>) = vertices.unzip();
+                let step = CHUNK_SIZE_METERS as f32 / morph.resolution as f32;
+                let positions = heights
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, height)| {
+                        let (x, z) = (i / (morph.resolution + 1), i % (morph.resolution + 1));
+                        [
+                            chunk.world_offset[0] as f32 + x as f32 * step,
+                            height,
+                            chunk.world_offset[1] as f32 + z as f32 * step,
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+            }
+            continue;
+        }
+
+        mesh.0 = meshes.add(chunk.generate_mesh());
+        let mut skirt_iter = skirts.iter_many_mut(children);
+        while let Some(mut skirt) = skirt_iter.fetch_next() {
+            skirt.0 = meshes.add(chunk.generate_skirt_mesh());
+        }
+        commands.entity(entity).remove::<Morph>();
+    }
+}
+
 #[derive(Component)]
 pub struct AlphaOverride;
 
@@ -0,0 +1,30 @@
+use crate::chunk::CHUNK_SIZE_METERS;
+
+// grid cells per side of a chunk and the distance up to which they are used, closest first
+const LOD_LEVELS: [(f32, usize); 4] = [(96.0, 64), (224.0, 32), (384.0, 16), (f32::INFINITY, 8)];
+// a chunk only turns coarser this far beyond its level, so it doesn't flip back and forth
+// while a loader moves along the border
+const LOD_HYSTERESIS: f32 = 24.0;
+// the rim mesh of the cup is built against a grid of one cell per metre
+pub const CUP_RESOLUTION: usize = CHUNK_SIZE_METERS;
+// grid cells per side of every chunk collider, whatever the resolution of its mesh, so the
+// ground the ball rolls on doesn't change as the camera moves
+pub const COLLIDER_RESOLUTION: usize = CUP_RESOLUTION;
+
+// Grid resolution of a chunk `distance` metres from the closest loader.
+pub fn resolution_for(distance: f32, current: Option<usize>) -> usize {
+    let level = |distance: f32| {
+        LOD_LEVELS
+            .iter()
+            .find(|(max_distance, _)| distance <= *max_distance)
+            .map_or(LOD_LEVELS[LOD_LEVELS.len() - 1].1, |(_, resolution)| {
+                *resolution
+            })
+    };
+
+    let wanted = level(distance);
+    match current {
+        Some(current) if current > wanted && level(distance - LOD_HYSTERESIS) == current => current,
+        _ => wanted,
+    }
+}
@@ -1,35 +1,54 @@
+pub mod cache;
 pub mod chunk_loader;
 pub mod chunk_manager;
+pub mod cup;
 pub mod generation;
+pub mod lod;
 
+use crate::add_chunk_collider::HasCollider;
 use crate::animation::{FadeOutAnimation, LiftDownAnimation};
+use crate::chunk::cache::{ChunkCache, ChunkCacheDirectory};
 use crate::chunk::chunk_manager::ChunkManager;
-use crate::chunk::generation::{WaterExtension, change_tree_material};
+use crate::chunk::cup::{CupGeometry, cutout_overlaps_square};
+use crate::chunk::generation::{WaterExtension, change_tree_material, triangle_corners};
 use crate::generation::Prop;
-use crate::generation::grasslands::GrasslandsGenerator;
-use crate::material::ground::Polynomial;
+use crate::generation::course_layout::CourseLayoutUniform;
+use crate::generation::registry::{GeneratorRegistry, apply_command_line};
+use crate::generation::validation::{CourseThresholds, PlayableCourse, create_playable};
+use crate::material::ground::GroundStyle;
 use bevy::app::{App, Plugin, Startup, Update};
-use bevy::asset::Asset;
 use bevy::input::ButtonInput;
+use bevy::math::Vec2;
 use bevy::pbr::ExtendedMaterial;
 use bevy::prelude::{
-    Commands, Component, Entity, KeyCode, PostUpdate, Query, Reflect, Res, ResMut, With, Without,
+    Added, Commands, Component, Entity, IntoScheduleConfigs, KeyCode, PostUpdate, Query, Reflect,
+    Res, ResMut, With, Without,
 };
 use bevy::prelude::{MaterialPlugin, StandardMaterial};
-use bevy::render::render_resource::{AsBindGroup, ShaderType};
+use bevy::render::render_resource::ShaderType;
+use bevy::tasks::{block_on, poll_once};
 
-pub(self) const CHUNK_SIZE_METERS: usize = 32;
-pub(self) const CHUNK_FIDELITY: usize = CHUNK_SIZE_METERS * 1;
+pub(crate) const CHUNK_SIZE_METERS: usize = 32;
 
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default, ShaderType)]
-pub struct Bunker {
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum HazardKind {
+    Bunker,
+    Pond,
+    // sandy scrub, played like rough
+    WasteArea,
+}
+
+// An elliptic hazard, `size` is the long radius, the short one is `size / 1.6`.
+#[derive(Reflect, Debug, Clone)]
+pub struct Hazard {
+    pub kind: HazardKind,
     pub x: f32,
     pub y: f32,
     pub rot: f32,
     pub size: f32,
 }
 
-impl Bunker {
+impl Hazard {
     pub fn dis(&self, x: f32, y: f32) -> f32 {
         let dx = self.x - x;
         let dy = self.y - y;
@@ -42,32 +61,80 @@ impl Bunker {
 
         (rx * rx + ry * ry).sqrt()
     }
+
+    // whether the hazard reaches into the square of `size` metres at `world_offset`
+    pub fn touches_square(&self, world_offset: [i32; 2], size: f32) -> bool {
+        let min = Vec2::new(world_offset[0] as f32, world_offset[1] as f32);
+        let center = Vec2::new(self.x, self.y);
+        center.clamp(min, min + size).distance(center) < self.size
+    }
+
+    pub fn uniform(&self) -> HazardUniform {
+        HazardUniform {
+            kind: self.kind as u32,
+            x: self.x,
+            y: self.y,
+            rot: self.rot,
+            size: self.size,
+        }
+    }
+}
+
+// One entry of the hazard storage buffer of the ground shader.
+#[derive(Reflect, Debug, Clone, Default, ShaderType)]
+pub struct HazardUniform {
+    kind: u32,
+    x: f32,
+    y: f32,
+    rot: f32,
+    size: f32,
 }
 
 #[derive(Component)]
 pub struct Chunk {
     world_offset: [i32; 2],
-    elevation: Box<[[f32; CHUNK_FIDELITY + 1]; CHUNK_FIDELITY + 1]>,
+    // grid cells per side, see `lod`
+    resolution: usize,
+    // (resolution + 1)² heights and normals, row by row along x
+    elevation: Vec<f32>,
+    normals: Vec<[f32; 3]>,
     props: Vec<Prop>,
-    course: Polynomial,
-    bunker: Bunker,
+    course: CourseLayoutUniform,
+    ground_style: GroundStyle,
+    hazards: Vec<Hazard>,
+    hole: [f32; 2],
+    // only the chunk containing the hole carries the cup
+    cup: Option<CupGeometry>,
 }
 
 impl Chunk {
     pub fn height_at(&self, sub_chunk_x: f32, sub_chunk_z: f32) -> Option<f32> {
-        // TODO: this code is hardcoded for 1mx1m mesh fidelity - update once chunk fidelity is up for change
-        let x_idx = sub_chunk_x.floor() as usize;
-        let z_idx = sub_chunk_z.floor() as usize;
-        let x_sub = sub_chunk_x % 1.0;
-        let z_sub = sub_chunk_z % 1.0;
+        let size = CHUNK_SIZE_METERS as f32;
+        if !(0.0..=size).contains(&sub_chunk_x) || !(0.0..=size).contains(&sub_chunk_z) {
+            return None;
+        }
 
-        let interpolated = self.elevation.get(z_idx)?.get(x_idx)? * (1.0 - z_sub) * (1.0 - x_sub)
-            + self.elevation.get(z_idx + 1)?.get(x_idx)? * z_sub * (1.0 - x_sub)
-            + self.elevation.get(z_idx)?.get(x_idx + 1)? * (1.0 - z_sub) * x_sub
-            + self.elevation.get(z_idx + 1)?.get(x_idx + 1)? * z_sub * x_sub;
+        // on the same triangles as the mesh and the collider, not bilinear across the cell
+        let interpolated = triangle_corners(self.resolution, sub_chunk_x, sub_chunk_z)
+            .into_iter()
+            .map(|((x, z), weight)| self.grid_height(x, z) * weight)
+            .sum();
 
         Some(interpolated)
     }
+
+    fn grid_height(&self, x: usize, z: usize) -> f32 {
+        self.elevation[x * (self.resolution + 1) + z]
+    }
+
+    fn grid_normal(&self, x: usize, z: usize) -> [f32; 3] {
+        self.normals[x * (self.resolution + 1) + z]
+    }
+
+    // whether part of the terrain of this chunk is cut away for the cup
+    pub fn overlaps_cup_cutout(&self) -> bool {
+        cutout_overlaps_square(self.hole, self.world_offset, CHUNK_SIZE_METERS as i32)
+    }
 }
 
 pub struct ChunkPlugin;
@@ -81,14 +148,48 @@ impl Plugin for ChunkPlugin {
         app.add_plugins(MaterialPlugin::<
             ExtendedMaterial<StandardMaterial, WaterExtension>,
         >::default())
-            .add_systems(Startup, move |mut commands: Commands| {
-                commands.insert_resource(ChunkManager::new(seed));
-            })
+            .init_resource::<GeneratorRegistry>()
+            .init_resource::<CourseThresholds>()
+            .init_resource::<ChunkCacheDirectory>()
+            .add_systems(
+                Startup,
+                (
+                    apply_command_line,
+                    move |mut registry: ResMut<GeneratorRegistry>,
+                          thresholds: Res<CourseThresholds>,
+                          cache_directory: Res<ChunkCacheDirectory>,
+                          mut commands: Commands| {
+                        let (generator, variant) =
+                            create_playable(&mut registry, &thresholds, seed);
+                        commands.insert_resource(ChunkManager::new(
+                            generator,
+                            &variant,
+                            ChunkCache::new(cache_directory.0.clone()),
+                        ));
+                    },
+                )
+                    .chain(),
+            )
             .add_systems(Update, generation::insert_chunk_mesh)
-            .add_systems(Update, chunk_manager::load_chunks)
-            .add_systems(Update, chunk_manager::unload_chunks)
+            .add_systems(
+                Update,
+                (generation::remesh_chunks, generation::morph_chunks).chain(),
+            )
+            // a new course and finished chunks go in before the loaders decide what to load,
+            // re-LOD or unload
+            .add_systems(
+                Update,
+                (
+                    receive_playable_course,
+                    chunk_manager::receive_generated_chunks,
+                    chunk_manager::load_chunks,
+                    chunk_manager::unload_chunks,
+                )
+                    .chain(),
+            )
             .add_systems(Update, generation::update_material_time)
             .add_systems(PostUpdate, despawn_unloaded_chunks)
+            .add_systems(PostUpdate, despawn_unloaded_colliders)
             .add_systems(Update, regenerate_on_r)
             .add_observer(change_tree_material);
     }
@@ -113,13 +214,37 @@ fn despawn_unloaded_chunks(
     }
 }
 
+// the collider goes as soon as the chunk is unloaded, not after its animation
+fn despawn_unloaded_colliders(query: Query<&HasCollider, Added<ToUnload>>, mut commands: Commands) {
+    for HasCollider(collider) in query {
+        commands.entity(*collider).despawn();
+    }
+}
+
 fn regenerate_on_r(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut registry: ResMut<GeneratorRegistry>,
+    thresholds: Res<CourseThresholds>,
     mut commands: Commands,
 ) {
     if keyboard_input.just_pressed(KeyCode::KeyR) {
         let seed = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs() as u32;
-        chunk_manager.replace_generator(&mut commands, Box::new(GrasslandsGenerator::new(seed)));
+        commands.insert_resource(PlayableCourse::spawn(&mut registry, &thresholds, seed));
     }
 }
+
+// the course built in the background replaces the current one once it is done
+pub(crate) fn receive_playable_course(
+    course: Option<ResMut<PlayableCourse>>,
+    mut chunk_manager: ResMut<ChunkManager>,
+    mut commands: Commands,
+) {
+    let Some(mut course) = course else {
+        return;
+    };
+    let Some((generator, variant)) = block_on(poll_once(&mut course.0)) else {
+        return;
+    };
+    chunk_manager.replace_generator(&mut commands, generator, &variant);
+    commands.remove_resource::<PlayableCourse>();
+}
@@ -0,0 +1,253 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::{
+    generation::ZoneType,
+    state::{aim::AimState, putting::Putting, state::AppState},
+    ui::shoot_challenge::AimChallengeState,
+};
+
+pub struct ClubPlugin;
+impl Plugin for ClubPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClubBag>().add_systems(
+            Update,
+            cycle_club_input_handler
+                .run_if(in_state(AppState::Aim))
+                .run_if(not(resource_exists::<Putting>))
+                .run_if(in_state(AimChallengeState::Idle)),
+        );
+    }
+}
+
+// strike multiplier ranges per zone, applied on top of the power the player hit
+pub struct PenaltyProfile {
+    pub fairway: Range<f32>,
+    pub first_cut: Range<f32>,
+    pub rough: Range<f32>,
+    pub offtrack: Range<f32>,
+    pub bunker: Range<f32>,
+}
+
+impl PenaltyProfile {
+    pub fn multiplier_range(&self, zone_type: &ZoneType) -> Range<f32> {
+        match zone_type {
+            ZoneType::DeadZone | ZoneType::Rock | ZoneType::Snow => 0.0..0.0,
+            ZoneType::Tee | ZoneType::Fairway | ZoneType::Green | ZoneType::Fringe => {
+                self.fairway.clone()
+            }
+            ZoneType::FirstCut => self.first_cut.clone(),
+            // hard-packed sand plays like the rough
+            ZoneType::Rough | ZoneType::WasteArea => self.rough.clone(),
+            ZoneType::Offtrack => self.offtrack.clone(),
+            ZoneType::Bunker => self.bunker.clone(),
+        }
+    }
+
+    pub fn multiplier(&self, zone_type: &ZoneType) -> f32 {
+        let range = self.multiplier_range(zone_type);
+        if range.is_empty() {
+            range.start
+        } else {
+            rand::random_range(range)
+        }
+    }
+
+    // middle of the range, for predictions
+    pub fn expected_multiplier(&self, zone_type: &ZoneType) -> f32 {
+        let range = self.multiplier_range(zone_type);
+        (range.start + range.end) * 0.5
+    }
+}
+
+pub struct Club {
+    pub name: &'static str,
+    // ball speed in m/s of a full power, centered strike
+    pub max_ball_speed: f32,
+    // backspin in rad/s of a full power, centered strike
+    pub backspin: f32,
+    // launch loft in degrees
+    pub loft: Range<f32>,
+    pub default_loft: f32,
+    // half width of the precision window around 0 on the shoot challenge scale
+    pub precision_grace: f32,
+    pub penalties: PenaltyProfile,
+}
+
+impl Club {
+    pub fn clamp_loft(&self, loft: f32) -> f32 {
+        loft.clamp(self.loft.start, self.loft.end)
+    }
+}
+
+pub static CLUBS: [Club; 8] = [
+    Club {
+        name: "Driver",
+        max_ball_speed: 70.0,
+        backspin: 280.0,
+        loft: 8.0..16.0,
+        default_loft: 12.0,
+        precision_grace: 0.06,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.9..0.96,
+            rough: 0.7..0.85,
+            offtrack: 0.5..0.7,
+            bunker: 0.1..0.2,
+        },
+    },
+    Club {
+        name: "3 Wood",
+        max_ball_speed: 62.0,
+        backspin: 380.0,
+        loft: 12.0..20.0,
+        default_loft: 15.0,
+        precision_grace: 0.07,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.9..0.97,
+            rough: 0.75..0.88,
+            offtrack: 0.6..0.8,
+            bunker: 0.15..0.3,
+        },
+    },
+    Club {
+        name: "5 Iron",
+        max_ball_speed: 56.0,
+        backspin: 560.0,
+        loft: 18.0..30.0,
+        default_loft: 21.0,
+        precision_grace: 0.08,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.92..0.98,
+            rough: 0.78..0.9,
+            offtrack: 0.65..0.85,
+            bunker: 0.2..0.4,
+        },
+    },
+    Club {
+        name: "7 Iron",
+        max_ball_speed: 51.0,
+        backspin: 700.0,
+        loft: 25.0..38.0,
+        default_loft: 28.0,
+        precision_grace: 0.09,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.93..0.98,
+            rough: 0.8..0.9,
+            offtrack: 0.7..0.85,
+            bunker: 0.25..0.45,
+        },
+    },
+    Club {
+        name: "9 Iron",
+        max_ball_speed: 45.0,
+        backspin: 850.0,
+        loft: 32.0..45.0,
+        default_loft: 38.0,
+        precision_grace: 0.1,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.94..0.99,
+            rough: 0.84..0.94,
+            offtrack: 0.75..0.9,
+            bunker: 0.3..0.5,
+        },
+    },
+    Club {
+        name: "Pitching Wedge",
+        max_ball_speed: 40.0,
+        backspin: 930.0,
+        loft: 38.0..50.0,
+        default_loft: 45.0,
+        precision_grace: 0.12,
+        penalties: PenaltyProfile {
+            fairway: 0.98..1.0,
+            first_cut: 0.95..0.99,
+            rough: 0.86..0.95,
+            offtrack: 0.8..0.9,
+            bunker: 0.4..0.6,
+        },
+    },
+    Club {
+        name: "Sand Wedge",
+        max_ball_speed: 34.0,
+        backspin: 1000.0,
+        loft: 45.0..60.0,
+        default_loft: 55.0,
+        precision_grace: 0.14,
+        penalties: PenaltyProfile {
+            fairway: 0.95..1.0,
+            first_cut: 0.95..0.99,
+            rough: 0.9..0.97,
+            offtrack: 0.85..0.95,
+            bunker: 0.8..0.95,
+        },
+    },
+    Club {
+        name: "Putter",
+        max_ball_speed: 9.0,
+        backspin: 0.0,
+        loft: 0.0..4.0,
+        default_loft: 2.0,
+        precision_grace: 0.2,
+        penalties: PenaltyProfile {
+            fairway: 0.99..1.0,
+            first_cut: 0.9..0.97,
+            rough: 0.6..0.8,
+            offtrack: 0.4..0.6,
+            bunker: 0.0..0.1,
+        },
+    },
+];
+
+#[derive(Resource, Default)]
+pub struct ClubBag {
+    selected: usize,
+}
+
+impl ClubBag {
+    pub fn selected(&self) -> &'static Club {
+        &CLUBS[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % CLUBS.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + CLUBS.len() - 1) % CLUBS.len();
+    }
+
+    // the putter is the last club in the bag
+    pub fn select_putter(&mut self) {
+        self.selected = CLUBS.len() - 1;
+    }
+}
+
+fn cycle_club_input_handler(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut club_bag: ResMut<ClubBag>,
+    aim_state: Option<ResMut<AimState>>,
+) {
+    let changed = if keyboard_input.just_pressed(KeyCode::KeyE) {
+        club_bag.next();
+        true
+    } else if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        club_bag.previous();
+        true
+    } else {
+        false
+    };
+
+    if changed {
+        info!("selected club {}", club_bag.selected().name);
+        // start every club at its natural loft
+        if let Some(mut aim_state) = aim_state {
+            aim_state.height = club_bag.selected().default_loft;
+        }
+    }
+}
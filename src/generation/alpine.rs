@@ -0,0 +1,302 @@
+use crate::chunk::{CHUNK_SIZE_METERS, Hazard, HazardKind};
+use crate::generation::course_layout::{CourseLayout, CourseStyle};
+use crate::generation::shaping::{
+    FIRST_CUT_WIDTH, HoleShaping, ROUGH_WIDTH, beside_path, bunker_depth,
+    clear_of_tee_and_each_other, greenside_bunkers, hazards_in_square, random_side, smoothstep,
+};
+use crate::generation::{Prop, PropType, TerrainGenerator, ZoneType, random_range};
+use bevy::math::{Vec2, Vec3};
+use noise::NoiseFn;
+use noise::Perlin;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::ops::Range;
+/* A course high up in the mountains.
+ * Pipeline (one time):
+ * 1. generate course / routes
+ *   - narrow fairways with sharp doglegs
+ *   - elevation profile from tee to green, usually downhill, with one steep drop along the way
+ * 2. place hazards
+ *   - a few bunkers at the landing zone and around the green, the mountains are hazard enough
+ * Pipeline (per pixel):
+ * 1. generate global noise map for faraway mountains
+ *   - ridged peaks on top of large hills
+ *   - terraced into shelves with cliffs between them
+ * 2. follow the elevation profile along the course, rising into the mountains beyond the rough
+ * 3. create water in deep valleys
+ * 4. set remaining material
+ *   - flattened tee pad and undulating green as inland
+ *   - bare rock on steep ground outside course area
+ *   - snow outside course area at high heights
+ *   - forests outside course area up to the treeline
+ */
+const COURSE_STYLE: CourseStyle = CourseStyle {
+    hole_shapes: [(120.0, 190.0, 1), (260.0, 370.0, 2), (430.0, 510.0, 3)],
+    dogleg: 0.3..0.8,
+    half_width: 14.0..24.0,
+    sway: 15.0,
+};
+const MOUNTAIN_BASE: f64 = 8.0;
+const MOUNTAIN_HEIGHT: f64 = 40.0;
+const RIDGE_HEIGHT: f64 = 20.0;
+// height of one shelf of the mountain sides, the steps between the shelves are the cliffs
+const CLIFF_STEP: f32 = 10.0;
+// share of a step taken up by its cliff, and how strongly the mountains are terraced
+const CLIFF_WIDTH: f32 = 0.25;
+const TERRACING: f32 = 0.7;
+// distance beyond the rough over which the ground rises from the course into the mountains
+const MOUNTAIN_BLEND: f32 = 50.0;
+// how far the green lies below or above the tee
+const ELEVATION_CHANGE: Range<f32> = 12.0..35.0;
+const UPHILL_CHANCE: f64 = 0.25;
+// lowest height of the course, well above the water
+const MIN_COURSE_HEIGHT: f32 = 2.0;
+// where the steepest drop lies, as a share of the length, and how many metres it takes
+const DROP_POSITION: Range<f32> = 0.25..0.6;
+const DROP_LENGTH: f32 = 40.0;
+// share of the elevation change taken by the drop, the rest is spread along the hole
+const DROP_SHARE: f32 = 0.6;
+const GREEN_UNDULATION_SCALE: f64 = 8.0;
+const GREEN_UNDULATION_HEIGHT: f64 = 0.3;
+const DRIVE_LANDING: f32 = 210.0;
+const SNOW_LINE: f32 = 40.0;
+// trees thin out over the last few metres below the treeline
+const TREELINE: f32 = 30.0;
+const TREELINE_FRAY: f32 = 6.0;
+// rise per run above which the ground outside the course is bare rock
+const ROCK_SLOPE: f32 = 1.0;
+// gusts funnel through the valleys
+const WIND_EXPOSURE: f32 = 1.25;
+const GROUND_TINT: Vec3 = Vec3::new(0.9, 1.05, 1.0);
+const WATER_HEIGHT: f32 = -5.0;
+
+// Height of the course along its length: a steady slope from tee to green with one steep drop.
+struct ElevationProfile {
+    tee: f32,
+    green: f32,
+    // arc length of the middle of the drop
+    drop_at: f32,
+    length: f32,
+}
+
+impl ElevationProfile {
+    fn height_at(&self, arc_length: f32) -> f32 {
+        let drop = smoothstep((arc_length - self.drop_at) / DROP_LENGTH + 0.5);
+        let slope = arc_length / self.length;
+        let t = slope * (1.0 - DROP_SHARE) + drop * DROP_SHARE;
+        self.tee + (self.green - self.tee) * t
+    }
+}
+
+pub struct AlpineGenerator {
+    seed: u32,
+    perlin: Perlin,
+    course: CourseLayout,
+    start: [f32; 2],
+    hole: [f32; 2],
+    profile: ElevationProfile,
+    shaping: HoleShaping,
+    hazards: Vec<Hazard>,
+}
+
+impl AlpineGenerator {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let perlin = Perlin::new(seed);
+
+        let course = CourseLayout::random(&mut rng, &COURSE_STYLE);
+        let start = course.start().to_array();
+        let hole = course.end().to_array();
+
+        let tee = mountain_height_at(&perlin, course.start()).clamp(MIN_COURSE_HEIGHT, TREELINE);
+        let change = random_range(&mut rng, ELEVATION_CHANGE.start, ELEVATION_CHANGE.end);
+        let downhill = !rng.random_bool(UPHILL_CHANCE) && tee - change > MIN_COURSE_HEIGHT;
+        let length = course.length();
+        let profile = ElevationProfile {
+            tee,
+            green: if downhill { tee - change } else { tee + change },
+            drop_at: length * random_range(&mut rng, DROP_POSITION.start, DROP_POSITION.end),
+            length,
+        };
+
+        let shaping = HoleShaping::new(&course, |p| {
+            profile.height_at(course.closest_point(p).arc_length) + rolling_height_at(&perlin, p)
+        });
+        let hazards = place_hazards(&course, &mut rng);
+
+        AlpineGenerator {
+            seed,
+            perlin,
+            course,
+            start,
+            hole,
+            profile,
+            shaping,
+            hazards,
+        }
+    }
+
+    fn natural_height_at(&self, p: Vec2) -> f32 {
+        let closest = self.course.closest_point(p);
+        let corridor =
+            self.profile.height_at(closest.arc_length) + rolling_height_at(&self.perlin, p);
+        let edge = closest.distance - closest.half_width - FIRST_CUT_WIDTH - ROUGH_WIDTH;
+        let wild = smoothstep(edge / MOUNTAIN_BLEND);
+        corridor + (mountain_height_at(&self.perlin, p) - corridor) * wild
+    }
+
+    // rise per run of the ground
+    fn slope_at(&self, p: Vec2) -> f32 {
+        let normal = self.normal_at(p.x, p.y);
+        Vec2::new(normal.x, normal.z).length() / normal.y
+    }
+}
+
+fn mountain_height_at(perlin: &Perlin, p: Vec2) -> f32 {
+    let (x, y) = (p.x as f64, p.y as f64);
+    let ridge = 1.0 - perlin.get([x / 150.0 + 300.0, y / 150.0]).abs();
+    let height = (MOUNTAIN_BASE
+        + perlin.get([x / 400.0, y / 400.0]) * MOUNTAIN_HEIGHT
+        + ridge * ridge * RIDGE_HEIGHT
+        + perlin.get([x / 40.0, y / 40.0]) * 2.0) as f32;
+
+    // flat shelves, each rising into a cliff at its end
+    let steps = height / CLIFF_STEP;
+    let shelf = steps.floor();
+    let cliff = smoothstep((steps - shelf - 1.0) / CLIFF_WIDTH + 1.0);
+    let terraced = (shelf + cliff) * CLIFF_STEP;
+    height + (terraced - height) * TERRACING
+}
+
+fn rolling_height_at(perlin: &Perlin, p: Vec2) -> f32 {
+    let (x, y) = (p.x as f64, p.y as f64);
+    (perlin.get([x / 14.0, y / 14.0]) * 0.15 + perlin.get([x / 50.0, y / 50.0])) as f32
+}
+
+impl TerrainGenerator for AlpineGenerator {
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    fn height_at(&self, x: f32, y: f32) -> f32 {
+        let p = Vec2::new(x, y);
+        let undulation = self.perlin.get([
+            x as f64 / GREEN_UNDULATION_SCALE + 100.0,
+            y as f64 / GREEN_UNDULATION_SCALE,
+        ]) * GREEN_UNDULATION_HEIGHT;
+        // the course never goes below MIN_COURSE_HEIGHT, no need to guard start and hole
+        self.shaping
+            .shape(p, self.natural_height_at(p), undulation as f32)
+            - bunker_depth(&self.hazards, p)
+    }
+
+    fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop> {
+        let approx_tree_count = ((self
+            .perlin
+            .get([offset.0 as f64 / 200.0, offset.1 as f64 / 200.0])
+            + 0.3)
+            * 6.0)
+            .max(0.0) as usize;
+        let seed = ((offset.0 as u64) << 16) ^ (offset.1 as u64) ^ ((self.seed as u64) << 32);
+        let mut random = StdRng::seed_from_u64(seed);
+
+        let mut result = Vec::new();
+
+        for _candidate in 0..approx_tree_count {
+            let x = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let z = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let y = self.height_at(x + offset.0 as f32, z + offset.1 as f32);
+            let treeline = TREELINE - random.random_range(0.0..TREELINE_FRAY);
+
+            if y < treeline
+                && self.zone_type_at(x + offset.0 as f32, z + offset.1 as f32) == ZoneType::Offtrack
+            {
+                result.push(Prop {
+                    prop_type: PropType::Tree,
+                    position: (x, y, z),
+                    seed: random.next_u32(),
+                });
+            }
+        }
+
+        result
+    }
+
+    fn course_layout(&self) -> &CourseLayout {
+        &self.course
+    }
+
+    fn start(&self) -> [f32; 2] {
+        self.start
+    }
+
+    fn hole(&self) -> [f32; 2] {
+        self.hole
+    }
+
+    fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
+        let p = Vec2::new(x, y);
+        let height = self.height_at(x, y);
+        if height <= WATER_HEIGHT {
+            ZoneType::DeadZone
+        } else if bunker_depth(&self.hazards, p) != 0.0 {
+            ZoneType::Bunker
+        } else {
+            match self.shaping.zone_at(&self.course, p) {
+                ZoneType::Offtrack if height > SNOW_LINE => ZoneType::Snow,
+                ZoneType::Offtrack if self.slope_at(p) > ROCK_SLOPE => ZoneType::Rock,
+                zone => zone,
+            }
+        }
+    }
+
+    fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard> {
+        hazards_in_square(&self.hazards, world_offset, CHUNK_SIZE_METERS as f32)
+    }
+
+    fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    fn wind_exposure(&self) -> f32 {
+        WIND_EXPOSURE
+    }
+
+    fn ground_tint(&self) -> Vec3 {
+        GROUND_TINT
+    }
+
+    fn snow_line(&self) -> f32 {
+        SNOW_LINE
+    }
+
+    fn rock_slope(&self) -> f32 {
+        ROCK_SLOPE
+    }
+}
+
+// A bunker at the landing zone of long holes and one or two around the green.
+fn place_hazards(course: &CourseLayout, rng: &mut StdRng) -> Vec<Hazard> {
+    let mut hazards: Vec<Hazard> = Vec::new();
+
+    if course.length() > 300.0 {
+        let size = random_range(rng, 6.0, 9.0);
+        let arc_length = DRIVE_LANDING + random_range(rng, -15.0, 15.0);
+        let side = random_side(rng);
+        // nips into the fairway
+        let offset = -size / 1.6 * 0.4;
+        hazards.push(beside_path(
+            course,
+            HazardKind::Bunker,
+            arc_length,
+            side,
+            offset,
+            size,
+        ));
+    }
+
+    let count = rng.random_range(1..=2);
+    hazards.extend(greenside_bunkers(course, rng, count, (6.0, 9.0)));
+
+    clear_of_tee_and_each_other(course, hazards)
+}
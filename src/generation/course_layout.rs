@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+use rand::Rng;
+use rand::rngs::StdRng;
+use std::ops::Range;
+
+use crate::generation::random_range;
+
+// number of points the spline is sampled into, keep in sync with ground_material.wgsl
+pub const PATH_SAMPLES: usize = 32;
+
+pub struct ControlPoint {
+    pub position: Vec2,
+    // distance from the centre line to the edge of the fairway
+    pub half_width: f32,
+}
+
+// How the holes of a biome are routed.
+pub struct CourseStyle {
+    // length range and number of legs for par 3, 4 and 5 holes
+    pub hole_shapes: [(f32, f32, usize); 3],
+    // how far a dogleg turns, in radians
+    pub dogleg: Range<f32>,
+    pub half_width: Range<f32>,
+    // how far the middle of a leg may bend away from the straight line
+    pub sway: f32,
+}
+
+// The point on the path closest to some position.
+pub struct PathPoint {
+    pub position: Vec2,
+    pub tangent: Vec2,
+    // metres along the path from the tee
+    pub arc_length: f32,
+    pub half_width: f32,
+    // distance of the queried position to `position`
+    pub distance: f32,
+}
+
+// The route of a hole from the tee to the hole: a Catmull-Rom spline through the control
+// points. It is sampled into a polyline once, all queries and the ground shader use that same
+// polyline, so the fairway looks exactly like it plays.
+#[derive(Clone)]
+pub struct CourseLayout {
+    // x, z, half width and arc length of every sample
+    samples: Vec<Vec4>,
+}
+
+impl CourseLayout {
+    pub fn new(control_points: &[ControlPoint]) -> Self {
+        assert!(control_points.len() >= 2);
+
+        let segments = control_points.len() - 1;
+        let point = |i: isize| &control_points[i.clamp(0, segments as isize) as usize];
+
+        let mut samples: Vec<Vec4> = Vec::with_capacity(PATH_SAMPLES);
+        for i in 0..PATH_SAMPLES {
+            let t = i as f32 / (PATH_SAMPLES - 1) as f32 * segments as f32;
+            let segment = (t.floor() as usize).min(segments - 1) as isize;
+            let local = t - segment as f32;
+
+            let position = catmull_rom(
+                point(segment - 1).position,
+                point(segment).position,
+                point(segment + 1).position,
+                point(segment + 2).position,
+                local,
+            );
+            let half_width = point(segment)
+                .half_width
+                .lerp(point(segment + 1).half_width, local);
+            let arc_length = samples
+                .last()
+                .map_or(0.0, |last| last.w + last.xy().distance(position));
+            samples.push(Vec4::new(position.x, position.y, half_width, arc_length));
+        }
+
+        CourseLayout { samples }
+    }
+
+    // A par 3, 4 or 5 hole with a random heading, walking the legs from the tee and turning at
+    // every corner.
+    pub fn random(rng: &mut StdRng, style: &CourseStyle) -> Self {
+        let (min_length, max_length, legs) = match random_range(rng, 0.0, 1.0) {
+            ..0.3 => style.hole_shapes[0],
+            ..0.75 => style.hole_shapes[1],
+            _ => style.hole_shapes[2],
+        };
+        let length = random_range(rng, min_length, max_length);
+        let mut heading = random_range(rng, 0.0, std::f32::consts::TAU);
+        let half_width =
+            |rng: &mut StdRng| random_range(rng, style.half_width.start, style.half_width.end);
+
+        let mut position = Vec2::ZERO;
+        let mut control_points = vec![ControlPoint {
+            position,
+            half_width: half_width(rng),
+        }];
+        for leg in 0..legs {
+            if leg > 0 {
+                let turn = random_range(rng, style.dogleg.start, style.dogleg.end);
+                heading += if rng.random_bool(0.5) { turn } else { -turn };
+            }
+            let direction = Vec2::from_angle(heading);
+            let leg_length = length / legs as f32;
+            let sway = direction.perp() * random_range(rng, -style.sway, style.sway);
+            control_points.push(ControlPoint {
+                position: position + direction * leg_length * 0.5 + sway,
+                half_width: half_width(rng),
+            });
+            position += direction * leg_length;
+            control_points.push(ControlPoint {
+                position,
+                half_width: half_width(rng),
+            });
+        }
+
+        CourseLayout::new(&control_points)
+    }
+
+    pub fn start(&self) -> Vec2 {
+        self.samples[0].xy()
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.samples[self.samples.len() - 1].xy()
+    }
+
+    pub fn length(&self) -> f32 {
+        self.samples[self.samples.len() - 1].w
+    }
+
+    pub fn closest_point(&self, p: Vec2) -> PathPoint {
+        let mut closest: Option<PathPoint> = None;
+        for pair in self.samples.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let along = b.xy() - a.xy();
+            let t = ((p - a.xy()).dot(along) / along.length_squared().max(f32::EPSILON))
+                .clamp(0.0, 1.0);
+            let position = a.xy() + along * t;
+            let distance = position.distance(p);
+            if closest
+                .as_ref()
+                .is_none_or(|closest| distance < closest.distance)
+            {
+                closest = Some(PathPoint {
+                    position,
+                    tangent: along.normalize_or(Vec2::X),
+                    arc_length: a.w.lerp(b.w, t),
+                    half_width: a.z.lerp(b.z, t),
+                    distance,
+                });
+            }
+        }
+        closest.unwrap()
+    }
+
+    pub fn distance_to_path(&self, p: Vec2) -> f32 {
+        self.closest_point(p).distance
+    }
+
+    pub fn on_fairway(&self, p: Vec2) -> bool {
+        let closest = self.closest_point(p);
+        closest.distance < closest.half_width
+    }
+
+    // position on the path `arc_length` metres from the tee
+    pub fn point_at(&self, arc_length: f32) -> Vec2 {
+        let (a, b, t) = self.segment_at(arc_length);
+        a.xy().lerp(b.xy(), t)
+    }
+
+    pub fn tangent_at(&self, arc_length: f32) -> Vec2 {
+        let (a, b, _) = self.segment_at(arc_length);
+        (b.xy() - a.xy()).normalize_or(Vec2::X)
+    }
+
+    pub fn half_width_at(&self, arc_length: f32) -> f32 {
+        let (a, b, t) = self.segment_at(arc_length);
+        a.z.lerp(b.z, t)
+    }
+
+    fn segment_at(&self, arc_length: f32) -> (Vec4, Vec4, f32) {
+        let arc_length = arc_length.clamp(0.0, self.length());
+        let index = self
+            .samples
+            .partition_point(|sample| sample.w <= arc_length)
+            .clamp(1, self.samples.len() - 1);
+        let (a, b) = (self.samples[index - 1], self.samples[index]);
+        let t = (arc_length - a.w) / (b.w - a.w).max(f32::EPSILON);
+        (a, b, t.clamp(0.0, 1.0))
+    }
+
+    pub fn uniform(&self) -> CourseLayoutUniform {
+        let mut samples = [Vec4::ZERO; PATH_SAMPLES];
+        samples.copy_from_slice(&self.samples);
+        CourseLayoutUniform {
+            samples,
+            count: PATH_SAMPLES as u32,
+        }
+    }
+}
+
+// The sampled path as handed to the ground shader.
+#[derive(Reflect, Debug, Clone, Default, ShaderType)]
+pub struct CourseLayoutUniform {
+    samples: [Vec4; PATH_SAMPLES],
+    count: u32,
+}
+
+fn catmull_rom(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, t: f32) -> Vec2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
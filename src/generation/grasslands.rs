@@ -1,117 +1,146 @@
-use crate::chunk::Bunker;
-use crate::generation::{Prop, PropType, TerrainGenerator, ZoneType};
-use crate::material::ground::Polynomial;
+use crate::chunk::{CHUNK_SIZE_METERS, Hazard, HazardKind};
+use crate::generation::course_layout::{CourseLayout, CourseStyle};
+use crate::generation::shaping::{
+    FIRST_CUT_WIDTH, HoleShaping, ROUGH_WIDTH, beside_path, bunker_depth,
+    clear_of_tee_and_each_other, greenside_bunkers, hazards_in_square, in_waste_area, random_side,
+};
+use crate::generation::{Prop, PropType, TerrainGenerator, ZoneType, random_range};
+use bevy::math::Vec2;
 use noise::NoiseFn;
 use noise::Perlin;
 use rand::rngs::StdRng;
 use rand::{Rng, RngCore, SeedableRng};
 /* Pipeline (one time):
- * 1. generate course / routes (single fixed line for now)
+ * 1. generate course / routes
+ *   - par 3, 4 or 5 length with a random heading
+ *   - doglegs between the legs of longer holes
  *   - start and end location
  * Pipeline (per pixel):
  * 1. generate local noise map
- * 2. generate global noise map for faraway mountains
- * 3. create water
- * 4. generate sand bunkers
- *   - thresholded noise map
- *   - can only appear on course
+ * 2. create water, dug out for ponds next to the fairway
+ * 3. generate sand bunkers
+ *   - placed once per course: at the landing zones of the tee shot and lay-up, and around the
+ *     green, leaving its front open
  *   - sandy pit with line of darker grass around it
- * 5. set remaining material
+ *   - waste areas of sandy scrub out in the rough
+ * 4. set remaining material
  *   - end rod at end rod location
- *   - smooth grass near end location
+ *   - flattened tee pad at start location
+ *   - smooth grass near end location, with a fringe around it
  *   - checkerboard grass everywhere else in course area
- *   - high grass at course area edge
+ *   - first cut and high grass at course area edge
  *   - high grass plus trees outside course area
- *   - stone/snow material outside course area at high heights
  */
+
+const COURSE_STYLE: CourseStyle = CourseStyle {
+    hole_shapes: [(130.0, 200.0, 1), (270.0, 390.0, 2), (450.0, 540.0, 3)],
+    dogleg: 0.15..0.6,
+    half_width: 18.0..30.0,
+    sway: 12.0,
+};
+const GREEN_UNDULATION_SCALE: f64 = 9.0;
+const GREEN_UNDULATION_HEIGHT: f64 = 0.25;
+// distance from the tee where drives land, and how far before the hole long holes are laid up
+const DRIVE_LANDING: f32 = 230.0;
+const LAY_UP_DISTANCE: f32 = 110.0;
+const POND_CHANCE: f32 = 0.4;
+const WASTE_AREA_CHANCE: f32 = 0.5;
+// below the water level in the middle of a pond
+const POND_DEPTH: f32 = 1.5;
+const WATER_HEIGHT: f32 = -5.0;
+
 pub struct GrasslandsGenerator {
     seed: u32,
     perlin: Perlin,
-    course: Polynomial,
+    course: CourseLayout,
     start: [f32; 2],
     hole: [f32; 2],
-}
-
-fn random_range(rng: &mut StdRng, min: f32, max: f32) -> f32 {
-    rng.next_u32() as f32 / u32::MAX as f32 * (max - min) + min
+    shaping: HoleShaping,
+    hazards: Vec<Hazard>,
 }
 
 impl GrasslandsGenerator {
     pub fn new(seed: u32) -> Self {
         let mut rng = StdRng::seed_from_u64(seed as u64);
+        let perlin = Perlin::new(seed);
 
-        let mut polynomial = Polynomial::default();
-        let hits = (
-            random_range(&mut rng, -40.0, 40.0),
-            random_range(&mut rng, -80.0, 80.0),
-            random_range(&mut rng, -80.0, 80.0),
-            random_range(&mut rng, -80.0, 80.0),
-        );
-        polynomial.d = hits.0;
-        for _ in 0..10 {
-            polynomial.c += (hits.1 - polynomial.f(100.0)) / 100.0;
-            polynomial.b += (hits.2 - polynomial.f(200.0)) / 200.0 / 200.0;
-            polynomial.a += (hits.3 - polynomial.f(300.0)) / 300.0 / 300.0 / 300.0;
-        }
-
-        let start = [0.0, polynomial.f(0.0)];
-        let hole = [300.0, polynomial.f(300.0)];
+        let course = CourseLayout::random(&mut rng, &COURSE_STYLE);
+        let start = course.start().to_array();
+        let hole = course.end().to_array();
+        let shaping = HoleShaping::new(&course, |p| {
+            local_height_at(&perlin, p.x as f64, p.y as f64) as f32
+        });
+        let hazards = place_hazards(&course, &mut rng);
 
         GrasslandsGenerator {
             seed,
-            perlin: Perlin::new(seed),
-            course: polynomial,
+            perlin,
+            course,
             start,
             hole,
+            shaping,
+            hazards,
         }
     }
 
-    fn bunker_depth(&self, x: f32, y: f32) -> f32 {
-        let chunk_x = ((x / 32.0).floor() as i32) * 32;
-        let chunk_y = ((y / 32.0).floor() as i32) * 32;
-        let bunker = self.nearest_bunker([chunk_x, chunk_y]);
-        let result = (1.0 - bunker.dis(x, y)).max(0.0);
-        result.sqrt() * 1.2
+    // natural terrain, flattened into a tee pad at the start and a gently undulating green
+    // around the hole
+    fn shaped_height_at(&self, x: f32, y: f32) -> f32 {
+        let height = local_height_at(&self.perlin, x as f64, y as f64) as f32;
+        let undulation = self.perlin.get([
+            x as f64 / GREEN_UNDULATION_SCALE + 100.0,
+            y as f64 / GREEN_UNDULATION_SCALE,
+        ]) * GREEN_UNDULATION_HEIGHT;
+        self.shaping
+            .shape(Vec2::new(x, y), height, undulation as f32)
     }
 
-    fn local_height_at(&self, x: f64, y: f64) -> f64 {
-        self.perlin.get([x / 24.0, y / 24.0]) * 0.15
-            + self.perlin.get([x / 60.0, y / 60.0])
-            + self.perlin.get([x / 240.0, y / 240.0]) * 6.0
+    // the water line of a pond follows its outline, the bed falls off towards the middle
+    fn dig_ponds(&self, x: f32, y: f32, height: f32) -> f32 {
+        self.hazards
+            .iter()
+            .filter(|hazard| hazard.kind == HazardKind::Pond)
+            .map(|pond| {
+                let dis = pond.dis(x, y);
+                WATER_HEIGHT + (dis * dis - 1.0) * POND_DEPTH
+            })
+            .fold(height, f32::min)
     }
 }
 
-fn dist(from: [f32; 2], to: [f32; 2]) -> f32 {
-    let dx = from[0] - to[0];
-    let dy = from[1] - to[1];
-    (dx * dx + dy * dy).sqrt()
+fn local_height_at(perlin: &Perlin, x: f64, y: f64) -> f64 {
+    perlin.get([x / 24.0, y / 24.0]) * 0.15
+        + perlin.get([x / 60.0, y / 60.0])
+        + perlin.get([x / 240.0, y / 240.0]) * 6.0
 }
 
 impl TerrainGenerator for GrasslandsGenerator {
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
     fn height_at(&self, x: f32, y: f32) -> f32 {
-        let height = self.local_height_at(x as f64, y as f64) as f32 - self.bunker_depth(x, y);
-        let dist_to_start_or_hole = dist(self.start(), [x, y]).min(dist(self.hole(), [x, y]));
-        // ensure start and hole are never underwater
-        let min_height = -3.85 - (dist_to_start_or_hole * 0.07).powi(4);
-        height.max(min_height)
+        let height = self.dig_ponds(x, y, self.shaped_height_at(x, y))
+            - bunker_depth(&self.hazards, Vec2::new(x, y));
+        self.shaping.keep_above_water(Vec2::new(x, y), height)
     }
 
     fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop> {
-        // TODO: don't hardcode chunk size and water height
+        // TODO: don't hardcode water height
         let approx_tree_count = ((self
             .perlin
             .get([offset.0 as f64 / 200.0, offset.1 as f64 / 200.0])
             + 0.1)
             * 5.0)
             .max(0.0) as usize;
-        let seed = ((offset.0 as u64) << 16) ^ (offset.1 as u64);
+        let seed = ((offset.0 as u64) << 16) ^ (offset.1 as u64) ^ ((self.seed as u64) << 32);
         let mut random = StdRng::seed_from_u64(seed);
 
         let mut result = Vec::new();
 
         for _candidate in 0..approx_tree_count {
-            let x = random.random_range(0.0..32.0);
-            let z = random.random_range(0.0..32.0);
+            let x = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let z = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
             let y = self.height_at(x + offset.0 as f32, z + offset.1 as f32);
 
             if self.zone_type_at(x + offset.0 as f32, z + offset.1 as f32) == ZoneType::Offtrack {
@@ -128,8 +157,8 @@ impl TerrainGenerator for GrasslandsGenerator {
         result
     }
 
-    fn course_layout(&self) -> Polynomial {
-        self.course.clone()
+    fn course_layout(&self) -> &CourseLayout {
+        &self.course
     }
 
     fn start(&self) -> [f32; 2] {
@@ -141,37 +170,99 @@ impl TerrainGenerator for GrasslandsGenerator {
     }
 
     fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
-        if self.height_at(x, y) <= -5.0 {
+        let p = Vec2::new(x, y);
+        if self.height_at(x, y) <= WATER_HEIGHT {
             ZoneType::DeadZone
-        } else if self.bunker_depth(x, y) != 0.0 {
+        } else if bunker_depth(&self.hazards, p) != 0.0 {
             ZoneType::Bunker
-        } else if self.course.on_clean_grass([x, y]) {
-            ZoneType::Clean
+        } else if in_waste_area(&self.hazards, p) {
+            ZoneType::WasteArea
         } else {
-            ZoneType::Offtrack
+            self.shaping.zone_at(&self.course, p)
         }
     }
 
-    fn nearest_bunker(&self, world_offset: [i32; 2]) -> Bunker {
-        let column = world_offset[0] | 63;
-        let mut random = StdRng::seed_from_u64(column as u64 | ((self.seed as u64) << 32));
+    fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard> {
+        hazards_in_square(&self.hazards, world_offset, CHUNK_SIZE_METERS as f32)
+    }
 
-        let x = column as f32 - random_range(&mut random, 29.0, 35.0);
-        let y = self.course.f(x) + random_range(&mut random, -170.0, 170.0);
+    fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
 
-        if self.course.approx_distance_to_curve([x, y]) >= 32.0 || x < 20.0 || 280.0 < x {
-            return Bunker {
-                x: -1_000_000.0,
-                y: -1_000_000.0,
-                rot: 0.0,
-                size: 0.0,
-            };
-        }
+    // 2: props depend on the seed of the course
+    fn version(&self) -> u32 {
+        2
+    }
+}
 
-        let rot = random_range(&mut random, 0.0, std::f32::consts::PI);
+// Lays out the hazards of the hole where they matter: flanking the landing zones, around the
+// green and along the fairway.
+fn place_hazards(course: &CourseLayout, rng: &mut StdRng) -> Vec<Hazard> {
+    let length = course.length();
+    let mut hazards: Vec<Hazard> = Vec::new();
 
-        let size = random_range(&mut random, 18.0, 28.0);
+    if random_range(rng, 0.0, 1.0) < POND_CHANCE && length > 200.0 {
+        let size = random_range(rng, 14.0, 22.0);
+        let arc_length = length * random_range(rng, 0.35, 0.7);
+        let side = random_side(rng);
+        let offset = size / 1.6 * 0.6;
+        hazards.push(beside_path(
+            course,
+            HazardKind::Pond,
+            arc_length,
+            side,
+            offset,
+            size,
+        ));
+    }
 
-        Bunker { x, y, rot, size }
+    let mut landing_zones = Vec::new();
+    if length > 300.0 {
+        landing_zones.push(DRIVE_LANDING);
     }
+    if length > 450.0 {
+        landing_zones.push(length - LAY_UP_DISTANCE);
+    }
+    for landing in landing_zones {
+        let sides = if rng.random_bool(1.0 / 3.0) {
+            vec![1.0, -1.0]
+        } else {
+            vec![random_side(rng)]
+        };
+        for side in sides {
+            let size = random_range(rng, 7.0, 11.0);
+            let arc_length = landing + random_range(rng, -15.0, 15.0);
+            // nips into the fairway
+            let offset = -size / 1.6 * 0.4;
+            hazards.push(beside_path(
+                course,
+                HazardKind::Bunker,
+                arc_length,
+                side,
+                offset,
+                size,
+            ));
+        }
+    }
+
+    let count = rng.random_range(1..=3);
+    hazards.extend(greenside_bunkers(course, rng, count, (6.0, 10.0)));
+
+    if random_range(rng, 0.0, 1.0) < WASTE_AREA_CHANCE {
+        let size = random_range(rng, 18.0, 30.0);
+        let arc_length = length * random_range(rng, 0.2, 0.8);
+        let side = random_side(rng);
+        let offset = FIRST_CUT_WIDTH + ROUGH_WIDTH * 0.5 + size / 1.6 * 0.5;
+        hazards.push(beside_path(
+            course,
+            HazardKind::WasteArea,
+            arc_length,
+            side,
+            offset,
+            size,
+        ));
+    }
+
+    clear_of_tee_and_each_other(course, hazards)
 }
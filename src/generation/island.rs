@@ -0,0 +1,397 @@
+use crate::chunk::{CHUNK_SIZE_METERS, Hazard, HazardKind};
+use crate::generation::course_layout::{CourseLayout, CourseStyle};
+use crate::generation::shaping::{
+    FIRST_CUT_WIDTH, FRINGE_WIDTH, GREEN_RADIUS, HoleShaping, ROUGH_WIDTH, beside_path,
+    bunker_depth, clear_of_tee_and_each_other, greenside_bunkers, hazards_in_square, random_side,
+};
+use crate::generation::{Prop, PropType, TerrainGenerator, ZoneType, random_range};
+use bevy::math::{Vec2, Vec3};
+use noise::NoiseFn;
+use noise::Perlin;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::ops::Range;
+/* A hole built around water that has to be carried.
+ * Pipeline (one time):
+ * 1. generate course / routes
+ * 2. lay out the water
+ *   - a lake all around the green, leaving it on an island
+ *   - a lake splitting the fairway
+ *   - a river winding across the course
+ * 3. shrink the water until every carry along the course is short enough
+ * 4. place hazards
+ *   - a bunker at the landing zone, greenside bunkers unless the green is an island
+ * Pipeline (per pixel):
+ * 1. generate local noise map of low, flat land
+ * 2. carve the water into it, with sloping banks
+ * 3. set remaining material as inland
+ */
+const COURSE_STYLE: CourseStyle = CourseStyle {
+    hole_shapes: [(130.0, 190.0, 1), (260.0, 380.0, 2), (440.0, 520.0, 3)],
+    dogleg: 0.15..0.5,
+    half_width: 18.0..28.0,
+    sway: 12.0,
+};
+// longest carry over water that is guaranteed to be enough, in metres
+pub const DEFAULT_MAX_CARRY: f32 = 160.0;
+// a carry starting closer to the tee than this has to be made from the tee
+const MIN_LAY_UP: f32 = 20.0;
+const MAX_SHRINKS: usize = 8;
+const SHRINK_FACTOR: f32 = 0.8;
+const ISLAND_GREEN_CHANCE: f64 = 0.35;
+const SPLIT_FAIRWAY_CHANCE: f64 = 0.5;
+const RIVER_CHANCE: f64 = 0.4;
+// dry ground between the fringe and the water of an island green
+const ISLAND_APRON: f32 = 3.0;
+// how far a river winds away from its straight line
+const RIVER_MEANDER: f32 = 12.0;
+// rise per metre away from the bank, and the deepest the water gets
+const BANK_SLOPE: f32 = 0.4;
+const WATER_DEPTH: f32 = 2.5;
+const WATER_HEIGHT: f32 = -5.0;
+const GREEN_UNDULATION_SCALE: f64 = 9.0;
+const GREEN_UNDULATION_HEIGHT: f64 = 0.25;
+const DRIVE_LANDING: f32 = 230.0;
+// nothing breaks the wind over open water
+const WIND_EXPOSURE: f32 = 1.15;
+const GROUND_TINT: Vec3 = Vec3::new(0.95, 1.05, 1.0);
+
+// Water deliberately laid across the hole.
+enum WaterBody {
+    // lake around the green, leaving it on an island
+    IslandLake {
+        hole: Vec2,
+        island_radius: f32,
+        radius: f32,
+    },
+    // lake across the fairway, `along` is the direction of the course through it
+    Lake {
+        center: Vec2,
+        along: Vec2,
+        half_length: f32,
+        half_width: f32,
+    },
+    // river winding across the course
+    River {
+        crossing: Vec2,
+        direction: Vec2,
+        width: f32,
+    },
+}
+
+impl WaterBody {
+    // distance from `p` to the bank, negative in the water
+    fn bank_distance(&self, perlin: &Perlin, p: Vec2) -> f32 {
+        match self {
+            WaterBody::IslandLake {
+                hole,
+                island_radius,
+                radius,
+            } => {
+                let distance = p.distance(*hole);
+                (island_radius - distance).max(distance - radius)
+            }
+            WaterBody::Lake {
+                center,
+                along,
+                half_length,
+                half_width,
+            } => {
+                let offset = p - *center;
+                let local = Vec2::new(
+                    offset.dot(*along) / half_length,
+                    offset.dot(along.perp()) / half_width,
+                );
+                (local.length() - 1.0) * half_length.min(*half_width)
+            }
+            WaterBody::River {
+                crossing,
+                direction,
+                width,
+            } => {
+                let offset = p - *crossing;
+                let along = offset.dot(*direction) as f64;
+                let meander = perlin.get([along / 90.0 + 500.0, 0.5]) as f32 * RIVER_MEANDER;
+                (offset.dot(direction.perp()) - meander).abs() - width * 0.5
+            }
+        }
+    }
+
+    // narrows the water where the course crosses it
+    fn shrink(&mut self, factor: f32) {
+        match self {
+            WaterBody::IslandLake {
+                island_radius,
+                radius,
+                ..
+            } => *radius = *island_radius + (*radius - *island_radius) * factor,
+            WaterBody::Lake { half_length, .. } => *half_length *= factor,
+            WaterBody::River { width, .. } => *width *= factor,
+        }
+    }
+}
+
+pub struct IslandGenerator {
+    seed: u32,
+    perlin: Perlin,
+    course: CourseLayout,
+    start: [f32; 2],
+    hole: [f32; 2],
+    shaping: HoleShaping,
+    water: Vec<WaterBody>,
+    hazards: Vec<Hazard>,
+}
+
+impl IslandGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self::with_max_carry(seed, DEFAULT_MAX_CARRY)
+    }
+
+    // every carry over water along the course is at most `max_carry` metres
+    pub fn with_max_carry(seed: u32, max_carry: f32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let perlin = Perlin::new(seed);
+
+        let course = CourseLayout::random(&mut rng, &COURSE_STYLE);
+        let start = course.start().to_array();
+        let hole = course.end().to_array();
+        let shaping = HoleShaping::new(&course, |p| local_height_at(&perlin, p));
+        let water = plan_water(&course, &mut rng);
+        let island_green = matches!(water.first(), Some(WaterBody::IslandLake { .. }));
+        let hazards = place_hazards(&course, &mut rng, island_green);
+
+        let mut generator = IslandGenerator {
+            seed,
+            perlin,
+            course,
+            start,
+            hole,
+            shaping,
+            water,
+            hazards,
+        };
+        for _ in 0..MAX_SHRINKS {
+            if generator.carries_within(max_carry) {
+                break;
+            }
+            for body in &mut generator.water {
+                body.shrink(SHRINK_FACTOR);
+            }
+        }
+        if !generator.carries_within(max_carry) {
+            generator.water.clear();
+        }
+        generator
+    }
+
+    // stretches of the course line under water, as arc lengths from the tee
+    fn water_crossings(&self) -> Vec<Range<f32>> {
+        let length = self.course.length();
+        let mut result = Vec::new();
+        let mut entered: Option<f32> = None;
+        for step in 0..=length.ceil() as usize {
+            let arc_length = (step as f32).min(length);
+            let p = self.course.point_at(arc_length);
+            let wet = self.height_at(p.x, p.y) <= WATER_HEIGHT;
+            match entered {
+                None if wet => entered = Some(arc_length),
+                Some(bank) if !wet => {
+                    result.push(bank..arc_length);
+                    entered = None;
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    // Every crossing can be carried from its near bank, and water starting right in front of
+    // the tee can be carried from the tee.
+    fn carries_within(&self, max_carry: f32) -> bool {
+        self.water_crossings()
+            .iter()
+            .enumerate()
+            .all(|(i, crossing)| {
+                let from_tee = i == 0 && crossing.start < MIN_LAY_UP;
+                crossing.end - crossing.start <= max_carry
+                    && (!from_tee || crossing.end <= max_carry)
+            })
+    }
+
+    fn carved_height_at(&self, p: Vec2) -> f32 {
+        let bank = self
+            .water
+            .iter()
+            .map(|body| body.bank_distance(&self.perlin, p))
+            .fold(f32::MAX, f32::min);
+        local_height_at(&self.perlin, p)
+            .min(WATER_HEIGHT + bank * BANK_SLOPE)
+            .max(WATER_HEIGHT - WATER_DEPTH)
+    }
+}
+
+// low and flat, the only water is the one laid out on purpose
+fn local_height_at(perlin: &Perlin, p: Vec2) -> f32 {
+    let (x, y) = (p.x as f64, p.y as f64);
+    (perlin.get([x / 24.0, y / 24.0]) * 0.15
+        + perlin.get([x / 60.0, y / 60.0])
+        + perlin.get([x / 240.0, y / 240.0]) * 2.0) as f32
+}
+
+impl TerrainGenerator for IslandGenerator {
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    fn height_at(&self, x: f32, y: f32) -> f32 {
+        let p = Vec2::new(x, y);
+        let undulation = self.perlin.get([
+            x as f64 / GREEN_UNDULATION_SCALE + 100.0,
+            y as f64 / GREEN_UNDULATION_SCALE,
+        ]) * GREEN_UNDULATION_HEIGHT;
+        let height = self
+            .shaping
+            .shape(p, self.carved_height_at(p), undulation as f32)
+            - bunker_depth(&self.hazards, p);
+        self.shaping.keep_above_water(p, height)
+    }
+
+    fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop> {
+        let approx_tree_count = ((self
+            .perlin
+            .get([offset.0 as f64 / 200.0, offset.1 as f64 / 200.0])
+            + 0.1)
+            * 4.0)
+            .max(0.0) as usize;
+        let seed = ((offset.0 as u64) << 16) ^ (offset.1 as u64) ^ ((self.seed as u64) << 32);
+        let mut random = StdRng::seed_from_u64(seed);
+
+        let mut result = Vec::new();
+
+        for _candidate in 0..approx_tree_count {
+            let x = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let z = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let y = self.height_at(x + offset.0 as f32, z + offset.1 as f32);
+
+            if self.zone_type_at(x + offset.0 as f32, z + offset.1 as f32) == ZoneType::Offtrack {
+                result.push(Prop {
+                    prop_type: PropType::Tree,
+                    position: (x, y, z),
+                    seed: random.next_u32(),
+                });
+            }
+        }
+
+        result
+    }
+
+    fn course_layout(&self) -> &CourseLayout {
+        &self.course
+    }
+
+    fn start(&self) -> [f32; 2] {
+        self.start
+    }
+
+    fn hole(&self) -> [f32; 2] {
+        self.hole
+    }
+
+    fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
+        let p = Vec2::new(x, y);
+        if self.height_at(x, y) <= WATER_HEIGHT {
+            ZoneType::DeadZone
+        } else if bunker_depth(&self.hazards, p) != 0.0 {
+            ZoneType::Bunker
+        } else {
+            self.shaping.zone_at(&self.course, p)
+        }
+    }
+
+    fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard> {
+        hazards_in_square(&self.hazards, world_offset, CHUNK_SIZE_METERS as f32)
+    }
+
+    fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    fn wind_exposure(&self) -> f32 {
+        WIND_EXPOSURE
+    }
+
+    fn ground_tint(&self) -> Vec3 {
+        GROUND_TINT
+    }
+}
+
+// Every par 3 and some longer holes get an island green, longer holes may have their fairway
+// split by a lake, and a river crosses the holes that would otherwise stay dry.
+fn plan_water(course: &CourseLayout, rng: &mut StdRng) -> Vec<WaterBody> {
+    let length = course.length();
+    let mut water = Vec::new();
+
+    if length < 250.0 || rng.random_bool(ISLAND_GREEN_CHANCE) {
+        let island_radius = GREEN_RADIUS + FRINGE_WIDTH + ISLAND_APRON;
+        water.push(WaterBody::IslandLake {
+            hole: course.end(),
+            island_radius,
+            radius: island_radius + random_range(rng, 20.0, 45.0),
+        });
+    }
+
+    if length > 250.0 && rng.random_bool(SPLIT_FAIRWAY_CHANCE) {
+        let arc_length = length * random_range(rng, 0.3, 0.6);
+        water.push(WaterBody::Lake {
+            center: course.point_at(arc_length),
+            along: course.tangent_at(arc_length),
+            half_length: random_range(rng, 15.0, 35.0),
+            // reaching well into the rough on both sides
+            half_width: course.half_width_at(arc_length)
+                + FIRST_CUT_WIDTH
+                + ROUGH_WIDTH
+                + random_range(rng, 10.0, 30.0),
+        });
+    }
+
+    if water.len() < 2 && (water.is_empty() || rng.random_bool(RIVER_CHANCE)) {
+        let arc_length = length * random_range(rng, 0.25, 0.75);
+        let angle = std::f32::consts::FRAC_PI_2 + random_range(rng, -0.5, 0.5);
+        water.push(WaterBody::River {
+            crossing: course.point_at(arc_length),
+            direction: Vec2::from_angle(angle).rotate(course.tangent_at(arc_length)),
+            width: random_range(rng, 8.0, 18.0),
+        });
+    }
+
+    water
+}
+
+// A bunker at the landing zone of long holes, and greenside bunkers when the green is not an
+// island.
+fn place_hazards(course: &CourseLayout, rng: &mut StdRng, island_green: bool) -> Vec<Hazard> {
+    let mut hazards: Vec<Hazard> = Vec::new();
+
+    if course.length() > 300.0 {
+        let size = random_range(rng, 7.0, 10.0);
+        let arc_length = DRIVE_LANDING + random_range(rng, -15.0, 15.0);
+        let side = random_side(rng);
+        // nips into the fairway
+        let offset = -size / 1.6 * 0.4;
+        hazards.push(beside_path(
+            course,
+            HazardKind::Bunker,
+            arc_length,
+            side,
+            offset,
+            size,
+        ));
+    }
+
+    if !island_green {
+        let count = rng.random_range(1..=2);
+        hazards.extend(greenside_bunkers(course, rng, count, (6.0, 9.0)));
+    }
+
+    clear_of_tee_and_each_other(course, hazards)
+}
@@ -0,0 +1,253 @@
+use crate::chunk::{CHUNK_SIZE_METERS, Hazard, HazardKind};
+use crate::generation::course_layout::{CourseLayout, CourseStyle};
+use crate::generation::shaping::{
+    FIRST_CUT_WIDTH, HoleShaping, ROUGH_WIDTH, beside_path, bunker_depth,
+    clear_of_tee_and_each_other, greenside_bunkers, hazards_in_square, in_waste_area, random_side,
+    smoothstep,
+};
+use crate::generation::{Prop, PropType, TerrainGenerator, ZoneType, random_range};
+use bevy::math::{Vec2, Vec3};
+use noise::NoiseFn;
+use noise::Perlin;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+/* A dry seaside course.
+ * Pipeline (one time):
+ * 1. generate course / routes
+ *   - wider fairways and gentler doglegs than inland
+ * 2. place hazards
+ *   - small, deep pot bunkers in the landing zones and around the green
+ *   - large waste bunkers along the rough, sometimes across the fairway
+ *   - no ponds
+ * Pipeline (per pixel):
+ * 1. generate rolling noise map for the humps of the fairways
+ * 2. raise dunes from ridged noise, stretched along the dune direction, towards the edge of
+ *    the course
+ * 3. set remaining material
+ *   - flattened tee pad and undulating green as inland
+ *   - dry, yellowed grass everywhere
+ *   - sparse scrub in the rough and waste areas
+ */
+const COURSE_STYLE: CourseStyle = CourseStyle {
+    hole_shapes: [(140.0, 210.0, 1), (280.0, 400.0, 2), (460.0, 540.0, 3)],
+    dogleg: 0.1..0.35,
+    half_width: 24.0..38.0,
+    sway: 20.0,
+};
+const DUNE_HEIGHT: f32 = 7.0;
+// distance from the edge of the fairway at which the dunes reach their full height
+const DUNE_DISTANCE: f32 = 30.0;
+const GREEN_UNDULATION_SCALE: f64 = 12.0;
+const GREEN_UNDULATION_HEIGHT: f64 = 0.35;
+const DRIVE_LANDING: f32 = 240.0;
+const LAY_UP_DISTANCE: f32 = 110.0;
+const CROSS_WASTE_CHANCE: f32 = 0.4;
+const MAX_SCRUB_PER_CHUNK: u32 = 3;
+// nothing breaks the wind by the sea
+const WIND_EXPOSURE: f32 = 1.6;
+const GROUND_TINT: Vec3 = Vec3::new(1.15, 1.0, 0.7);
+
+pub struct LinksGenerator {
+    seed: u32,
+    perlin: Perlin,
+    course: CourseLayout,
+    start: [f32; 2],
+    hole: [f32; 2],
+    shaping: HoleShaping,
+    hazards: Vec<Hazard>,
+    // the ridges of the dunes run along this direction
+    dune_direction: Vec2,
+}
+
+impl LinksGenerator {
+    pub fn new(seed: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let perlin = Perlin::new(seed);
+
+        let course = CourseLayout::random(&mut rng, &COURSE_STYLE);
+        let start = course.start().to_array();
+        let hole = course.end().to_array();
+        // tee and green lie on the course, where the dunes are flattened out
+        let shaping = HoleShaping::new(&course, |p| rolling_height_at(&perlin, p));
+        let hazards = place_hazards(&course, &mut rng);
+        let dune_direction = Vec2::from_angle(random_range(&mut rng, 0.0, std::f32::consts::PI));
+
+        LinksGenerator {
+            seed,
+            perlin,
+            course,
+            start,
+            hole,
+            shaping,
+            hazards,
+            dune_direction,
+        }
+    }
+
+    fn dune_height_at(&self, p: Vec2) -> f32 {
+        let along = p.dot(self.dune_direction) as f64;
+        let across = p.dot(self.dune_direction.perp()) as f64;
+        let ridge = 1.0 - self.perlin.get([along / 90.0 + 50.0, across / 30.0]).abs() as f32;
+        ridge * ridge * DUNE_HEIGHT
+    }
+
+    fn natural_height_at(&self, p: Vec2) -> f32 {
+        let closest = self.course.closest_point(p);
+        let dunes = smoothstep((closest.distance - closest.half_width) / DUNE_DISTANCE);
+        rolling_height_at(&self.perlin, p) + self.dune_height_at(p) * dunes
+    }
+}
+
+fn rolling_height_at(perlin: &Perlin, p: Vec2) -> f32 {
+    let (x, y) = (p.x as f64, p.y as f64);
+    (perlin.get([x / 15.0, y / 15.0]) * 0.25 + perlin.get([x / 45.0, y / 45.0]) * 1.2) as f32
+}
+
+impl TerrainGenerator for LinksGenerator {
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    fn height_at(&self, x: f32, y: f32) -> f32 {
+        let p = Vec2::new(x, y);
+        let undulation = self.perlin.get([
+            x as f64 / GREEN_UNDULATION_SCALE + 100.0,
+            y as f64 / GREEN_UNDULATION_SCALE,
+        ]) * GREEN_UNDULATION_HEIGHT;
+        // always well above the water, no need to guard start and hole
+        self.shaping
+            .shape(p, self.natural_height_at(p), undulation as f32)
+            - bunker_depth(&self.hazards, p)
+    }
+
+    fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop> {
+        let seed = ((offset.0 as u64) << 16) ^ (offset.1 as u64) ^ ((self.seed as u64) << 32);
+        let mut random = StdRng::seed_from_u64(seed);
+
+        let mut result = Vec::new();
+
+        for _candidate in 0..random.random_range(0..=MAX_SCRUB_PER_CHUNK) {
+            let x = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let z = random.random_range(0.0..CHUNK_SIZE_METERS as f32);
+            let y = self.height_at(x + offset.0 as f32, z + offset.1 as f32);
+
+            if matches!(
+                self.zone_type_at(x + offset.0 as f32, z + offset.1 as f32),
+                ZoneType::Rough | ZoneType::Offtrack | ZoneType::WasteArea
+            ) {
+                result.push(Prop {
+                    prop_type: PropType::Scrub,
+                    position: (x, y, z),
+                    seed: random.next_u32(),
+                });
+            }
+        }
+
+        result
+    }
+
+    fn course_layout(&self) -> &CourseLayout {
+        &self.course
+    }
+
+    fn start(&self) -> [f32; 2] {
+        self.start
+    }
+
+    fn hole(&self) -> [f32; 2] {
+        self.hole
+    }
+
+    fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
+        let p = Vec2::new(x, y);
+        if bunker_depth(&self.hazards, p) != 0.0 {
+            ZoneType::Bunker
+        } else if in_waste_area(&self.hazards, p) {
+            ZoneType::WasteArea
+        } else {
+            self.shaping.zone_at(&self.course, p)
+        }
+    }
+
+    fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard> {
+        hazards_in_square(&self.hazards, world_offset, CHUNK_SIZE_METERS as f32)
+    }
+
+    fn hazards(&self) -> &[Hazard] {
+        &self.hazards
+    }
+
+    fn wind_exposure(&self) -> f32 {
+        WIND_EXPOSURE
+    }
+
+    fn ground_tint(&self) -> Vec3 {
+        GROUND_TINT
+    }
+}
+
+// Pot bunkers dot the landing zones and surround the green, large waste bunkers line the
+// rough and on longer holes sometimes have to be carried.
+fn place_hazards(course: &CourseLayout, rng: &mut StdRng) -> Vec<Hazard> {
+    let length = course.length();
+    let mut hazards: Vec<Hazard> = Vec::new();
+
+    if length > 300.0 && random_range(rng, 0.0, 1.0) < CROSS_WASTE_CHANCE {
+        let arc_length = length * random_range(rng, 0.3, 0.45);
+        let center = course.point_at(arc_length);
+        hazards.push(Hazard {
+            kind: HazardKind::WasteArea,
+            x: center.x,
+            y: center.y,
+            // long side across the fairway
+            rot: course.tangent_at(arc_length).to_angle() + std::f32::consts::FRAC_PI_2,
+            size: random_range(rng, 35.0, 45.0),
+        });
+    }
+
+    let mut landing_zones = Vec::new();
+    if length > 300.0 {
+        landing_zones.push(DRIVE_LANDING);
+    }
+    if length > 450.0 {
+        landing_zones.push(length - LAY_UP_DISTANCE);
+    }
+    for landing in landing_zones {
+        for _ in 0..rng.random_range(2..=3) {
+            let size = random_range(rng, 3.5, 5.5);
+            let arc_length = landing + random_range(rng, -25.0, 25.0);
+            let side = random_side(rng);
+            // anywhere from the middle to the edge of the fairway
+            let half_width = course.half_width_at(arc_length);
+            let offset = -half_width * random_range(rng, 0.1, 0.9);
+            hazards.push(beside_path(
+                course,
+                HazardKind::Bunker,
+                arc_length,
+                side,
+                offset,
+                size,
+            ));
+        }
+    }
+
+    let count = rng.random_range(2..=4);
+    hazards.extend(greenside_bunkers(course, rng, count, (3.5, 5.5)));
+
+    for _ in 0..rng.random_range(1..=2) {
+        let size = random_range(rng, 30.0, 50.0);
+        let arc_length = length * random_range(rng, 0.15, 0.85);
+        let side = random_side(rng);
+        let offset = FIRST_CUT_WIDTH + ROUGH_WIDTH * 0.5 + size / 1.6 * 0.5;
+        hazards.push(beside_path(
+            course,
+            HazardKind::WasteArea,
+            arc_length,
+            side,
+            offset,
+            size,
+        ));
+    }
+
+    clear_of_tee_and_each_other(course, hazards)
+}
@@ -1,12 +1,28 @@
-use crate::chunk::Bunker;
-use crate::material::ground::Polynomial;
+use crate::chunk::Hazard;
+use crate::generation::course_layout::CourseLayout;
+use bevy::math::Vec3;
+use rand::RngCore;
+use rand::rngs::StdRng;
 
+pub mod alpine;
+pub mod course_layout;
 pub mod grasslands;
+pub mod island;
+pub mod links;
+pub mod registry;
+pub mod shaping;
+pub mod validation;
 
+// metres between the height samples of `TerrainGenerator::normal_at`
+const NORMAL_SAMPLE_DISTANCE: f32 = 0.25;
+
+#[derive(Clone, Copy)]
 pub enum PropType {
     Tree,
+    Scrub,
 }
 
+#[derive(Clone)]
 pub struct Prop {
     pub prop_type: PropType,
     pub position: (f32, f32, f32),
@@ -16,17 +32,97 @@ pub struct Prop {
 #[derive(PartialEq)]
 pub enum ZoneType {
     DeadZone,
-    Clean,
+    Tee,
+    Fairway,
+    // short band of slightly longer grass between fairway and rough
+    FirstCut,
+    Rough,
     Offtrack,
     Bunker,
+    WasteArea,
+    Green,
+    // collar of longer grass around the green
+    Fringe,
+    // unplayable ground high up in the mountains
+    Rock,
+    Snow,
+}
+
+impl ZoneType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ZoneType::DeadZone => "WATER",
+            ZoneType::Tee => "TEE",
+            ZoneType::Fairway => "FAIRWAY",
+            ZoneType::FirstCut => "FIRST CUT",
+            ZoneType::Rough => "ROUGH",
+            ZoneType::Offtrack => "OFF COURSE",
+            ZoneType::Bunker => "BUNKER",
+            ZoneType::WasteArea => "WASTE AREA",
+            ZoneType::Green => "GREEN",
+            ZoneType::Fringe => "FRINGE",
+            ZoneType::Rock => "ROCK",
+            ZoneType::Snow => "SNOW",
+        }
+    }
+
+    // the ball cannot be played from here and has to be dropped with a penalty
+    pub fn is_unplayable(&self) -> bool {
+        matches!(self, ZoneType::DeadZone | ZoneType::Rock | ZoneType::Snow)
+    }
 }
 
 pub trait TerrainGenerator {
+    fn seed(&self) -> u32;
     fn height_at(&self, x: f32, y: f32) -> f32;
+    // upward normal of the ground, the same on both sides of a chunk border
+    fn normal_at(&self, x: f32, y: f32) -> Vec3 {
+        normal_from_heights(self, x, y, NORMAL_SAMPLE_DISTANCE)
+    }
     fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop>;
-    fn course_layout(&self) -> Polynomial;
+    fn course_layout(&self) -> &CourseLayout;
     fn start(&self) -> [f32; 2];
     fn hole(&self) -> [f32; 2];
     fn zone_type_at(&self, x: f32, y: f32) -> ZoneType;
-    fn nearest_bunker(&self, world_offset: [i32; 2]) -> Bunker;
+    // every bunker, pond and waste area reaching into the chunk at `world_offset`
+    fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard>;
+    // every bunker, pond and waste area of the course
+    fn hazards(&self) -> &[Hazard];
+    // multiplier on the wind speed, open courses are more exposed
+    fn wind_exposure(&self) -> f32 {
+        1.0
+    }
+    // multiplied onto the colour of the grass
+    fn ground_tint(&self) -> Vec3 {
+        Vec3::ONE
+    }
+    // height above which the ground outside the course is covered in snow, by default never
+    fn snow_line(&self) -> f32 {
+        f32::MAX
+    }
+    // rise per run above which the ground outside the course is bare rock, by default never
+    fn rock_slope(&self) -> f32 {
+        f32::MAX
+    }
+    // bump whenever the generator builds different terrain for the same seed and options, so
+    // its chunks in the cache are generated again while those of other generators are kept
+    fn version(&self) -> u32 {
+        1
+    }
+}
+
+// Normal from central differences of the height, `spacing` metres to either side.
+fn normal_from_heights<G: TerrainGenerator + ?Sized>(
+    generator: &G,
+    x: f32,
+    y: f32,
+    spacing: f32,
+) -> Vec3 {
+    let dx = generator.height_at(x + spacing, y) - generator.height_at(x - spacing, y);
+    let dz = generator.height_at(x, y + spacing) - generator.height_at(x, y - spacing);
+    Vec3::new(-dx, 2.0 * spacing, -dz).normalize()
+}
+
+pub(crate) fn random_range(rng: &mut StdRng, min: f32, max: f32) -> f32 {
+    rng.next_u32() as f32 / u32::MAX as f32 * (max - min) + min
 }
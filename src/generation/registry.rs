@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::chunk::cache::ChunkCacheDirectory;
+use crate::generation::TerrainGenerator;
+use crate::generation::alpine::AlpineGenerator;
+use crate::generation::grasslands::GrasslandsGenerator;
+use crate::generation::island::{DEFAULT_MAX_CARRY, IslandGenerator};
+use crate::generation::links::LinksGenerator;
+use crate::generation::random_range;
+
+pub type BoxedGenerator = Box<dyn TerrainGenerator + Send + Sync>;
+pub type GeneratorFactory = Arc<dyn Fn(u32, &GeneratorOptions) -> BoxedGenerator + Send + Sync>;
+
+// Settings handed to every generator factory, e.g. `max_carry` for the island generator.
+#[derive(Default, Clone)]
+pub struct GeneratorOptions(HashMap<String, f32>);
+
+impl GeneratorOptions {
+    pub fn get(&self, key: &str) -> Option<f32> {
+        self.0.get(key).copied()
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: f32) {
+        self.0.insert(key.into(), value);
+    }
+
+    // longest carry over water a course may ask for, built into island courses and checked
+    // by the validation of every course
+    pub fn max_carry(&self) -> f32 {
+        self.get("max_carry").unwrap_or(DEFAULT_MAX_CARRY)
+    }
+}
+
+// Which generator builds the next course.
+#[derive(Clone, PartialEq, Debug)]
+pub enum GeneratorSelection {
+    // picked from the seed of every course, by the weights of the generators
+    Random,
+    // every registered generator in turn
+    Rotation,
+    Named(String),
+}
+
+impl GeneratorSelection {
+    pub fn label(&self) -> String {
+        match self {
+            GeneratorSelection::Random => String::from("RANDOM"),
+            GeneratorSelection::Rotation => String::from("ROTATION"),
+            GeneratorSelection::Named(name) => name.to_uppercase(),
+        }
+    }
+}
+
+struct RegisteredGenerator {
+    name: String,
+    // share of the randomly picked courses
+    weight: f32,
+    factory: GeneratorFactory,
+}
+
+// Every terrain generator the game knows by name. Plugins add their own with
+// `App::register_generator`.
+#[derive(Resource)]
+pub struct GeneratorRegistry {
+    generators: Vec<RegisteredGenerator>,
+    pub selection: GeneratorSelection,
+    pub options: GeneratorOptions,
+    // next generator in rotation
+    rotation: usize,
+}
+
+impl Default for GeneratorRegistry {
+    fn default() -> Self {
+        let mut registry = GeneratorRegistry {
+            generators: Vec::new(),
+            selection: GeneratorSelection::Random,
+            options: GeneratorOptions::default(),
+            rotation: 0,
+        };
+        registry
+            .register("grasslands", 0.35, |seed, _| {
+                Box::new(GrasslandsGenerator::new(seed))
+            })
+            .register("links", 0.3, |seed, _| Box::new(LinksGenerator::new(seed)))
+            .register("alpine", 0.2, |seed, _| {
+                Box::new(AlpineGenerator::new(seed))
+            })
+            .register("island", 0.15, |seed, options| {
+                Box::new(IslandGenerator::with_max_carry(seed, options.max_carry()))
+            });
+        registry
+    }
+}
+
+impl GeneratorRegistry {
+    // a generator registered under an existing name replaces it
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        weight: f32,
+        factory: impl Fn(u32, &GeneratorOptions) -> BoxedGenerator + Send + Sync + 'static,
+    ) -> &mut Self {
+        let generator = RegisteredGenerator {
+            name: name.into(),
+            weight,
+            factory: Arc::new(factory),
+        };
+        match self
+            .generators
+            .iter_mut()
+            .find(|g| g.name == generator.name)
+        {
+            Some(existing) => *existing = generator,
+            None => self.generators.push(generator),
+        }
+        self
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.generators
+            .iter()
+            .any(|generator| generator.name == name)
+    }
+
+    // everything the player can pick from
+    pub fn choices(&self) -> Vec<GeneratorSelection> {
+        let mut result = vec![GeneratorSelection::Random, GeneratorSelection::Rotation];
+        result.extend(
+            self.generators
+                .iter()
+                .map(|generator| GeneratorSelection::Named(generator.name.clone())),
+        );
+        result
+    }
+
+    // the generator of the next course, as selected
+    pub fn create(&mut self, seed: u32) -> BoxedGenerator {
+        let index = self.pick(seed);
+        self.build(index, seed)
+    }
+
+    // index of the generator of the next course, advances the rotation
+    pub fn pick(&mut self, seed: u32) -> usize {
+        match self.selection.clone() {
+            GeneratorSelection::Random => self.pick_by_weight(seed),
+            GeneratorSelection::Rotation => {
+                let index = self.rotation % self.generators.len();
+                self.rotation += 1;
+                index
+            }
+            GeneratorSelection::Named(name) => self
+                .generators
+                .iter()
+                .position(|generator| generator.name == name)
+                .unwrap_or_else(|| {
+                    warn!("unknown generator {name}, picking one at random");
+                    self.pick_by_weight(seed)
+                }),
+        }
+    }
+
+    pub fn build(&self, index: usize, seed: u32) -> BoxedGenerator {
+        (self.generators[index].factory)(seed, &self.options)
+    }
+
+    // `build` without the registry, e.g. on another thread
+    pub fn builder(&self, index: usize) -> impl Fn(u32) -> BoxedGenerator + Send + 'static {
+        let factory = self.generators[index].factory.clone();
+        let options = self.options.clone();
+        move |seed| factory(seed, &options)
+    }
+
+    // the generator with the options handed to it, which together with the seed decide the
+    // terrain, e.g. `island_max_carry=120`
+    pub fn variant(&self, index: usize) -> String {
+        let mut options = self.options.0.iter().collect::<Vec<_>>();
+        options.sort_by(|a, b| a.0.cmp(b.0));
+        let mut result = self.generators[index].name.clone();
+        for (key, value) in options {
+            result.push_str(&format!("_{key}={value}"));
+        }
+        result
+    }
+
+    // the same seed always picks the same generator
+    fn pick_by_weight(&self, seed: u32) -> usize {
+        let mut rng = StdRng::seed_from_u64(seed as u64 ^ 0xb10e);
+        let total: f32 = self.generators.iter().map(|g| g.weight).sum();
+        let mut roll = random_range(&mut rng, 0.0, total);
+        for (i, generator) in self.generators.iter().enumerate() {
+            if roll < generator.weight {
+                return i;
+            }
+            roll -= generator.weight;
+        }
+        self.generators.len() - 1
+    }
+}
+
+pub trait RegisterGenerator {
+    fn register_generator(
+        &mut self,
+        name: impl Into<String>,
+        weight: f32,
+        factory: impl Fn(u32, &GeneratorOptions) -> BoxedGenerator + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl RegisterGenerator for App {
+    fn register_generator(
+        &mut self,
+        name: impl Into<String>,
+        weight: f32,
+        factory: impl Fn(u32, &GeneratorOptions) -> BoxedGenerator + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<GeneratorRegistry>()
+            .register(name, weight, factory);
+        self
+    }
+}
+
+// every flag of the game, each followed by a value
+const FLAGS: [&str; 3] = ["--generator", "--generator-option", "--chunk-cache"];
+
+// `--generator <name|random|rotation>` picks the generator, every
+// `--generator-option <key>=<value>` is handed to the factories and `--chunk-cache <dir>`
+// keeps generated chunks on disk.
+pub(crate) fn apply_command_line(
+    mut registry: ResMut<GeneratorRegistry>,
+    mut cache_directory: ResMut<ChunkCacheDirectory>,
+) {
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        // only known flags take the next argument as their value
+        if !FLAGS.contains(&flag.as_str()) {
+            warn!("ignoring unknown argument {flag}");
+            continue;
+        }
+        let Some(value) = args.next() else {
+            warn!("ignoring {flag} without a value");
+            break;
+        };
+
+        match flag.as_str() {
+            "--generator" => {
+                registry.selection = match value.as_str() {
+                    "random" => GeneratorSelection::Random,
+                    "rotation" => GeneratorSelection::Rotation,
+                    name if registry.contains(name) => GeneratorSelection::Named(name.into()),
+                    name => {
+                        warn!("unknown generator {name}, picking one at random");
+                        GeneratorSelection::Random
+                    }
+                };
+            }
+            "--generator-option" => {
+                match value
+                    .split_once('=')
+                    .and_then(|(key, value)| Some((key, value.parse().ok()?)))
+                {
+                    Some((key, number)) => registry.options.set(key, number),
+                    None => warn!("ignoring generator option {value}, expected <key>=<number>"),
+                }
+            }
+            // `--chunk-cache`, the last of `FLAGS`
+            _ => cache_directory.0 = Some(PathBuf::from(value)),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::math::Vec2;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::chunk::{Hazard, HazardKind};
+use crate::generation::course_layout::CourseLayout;
+use crate::generation::{ZoneType, random_range};
+
+// The parts of a hole every biome shares: tee pad, green, the cuts of grass along the course
+// and the hazards placed along it.
+
+// sizes of the zones, keep in sync with ground_material.wgsl
+pub const GREEN_RADIUS: f32 = 15.0;
+pub const FRINGE_WIDTH: f32 = 1.5;
+pub const TEE_HALF_LENGTH: f32 = 6.0;
+pub const TEE_HALF_WIDTH: f32 = 4.0;
+pub const FIRST_CUT_WIDTH: f32 = 2.5;
+pub const ROUGH_WIDTH: f32 = 10.0;
+// distance over which tee pad and green blend into the surrounding terrain
+pub const SHAPING_BLEND: f32 = 8.0;
+const TEE_LIFT: f32 = 0.3;
+// lowest base height of tee pad and green, safely above the water
+const MIN_SHAPED_HEIGHT: f32 = -3.6;
+
+pub struct HoleShaping {
+    start: Vec2,
+    hole: Vec2,
+    // direction the tee pad points in, along the start of the course
+    tee_direction: Vec2,
+    tee_height: f32,
+    green_height: f32,
+}
+
+impl HoleShaping {
+    // `base_height` is the unshaped terrain that tee pad and green are levelled to
+    pub fn new(course: &CourseLayout, base_height: impl Fn(Vec2) -> f32) -> Self {
+        HoleShaping {
+            start: course.start(),
+            hole: course.end(),
+            tee_direction: course.tangent_at(0.0),
+            tee_height: base_height(course.start()).max(MIN_SHAPED_HEIGHT) + TEE_LIFT,
+            green_height: base_height(course.end()).max(MIN_SHAPED_HEIGHT),
+        }
+    }
+
+    // how far a point lies outside the tee pad, 0 on it
+    pub fn distance_to_tee(&self, p: Vec2) -> f32 {
+        let offset = p - self.start;
+        let local = Vec2::new(
+            offset.dot(self.tee_direction),
+            offset.dot(self.tee_direction.perp()),
+        );
+        (local.abs() - Vec2::new(TEE_HALF_LENGTH, TEE_HALF_WIDTH))
+            .max(Vec2::ZERO)
+            .length()
+    }
+
+    // natural terrain `height`, flattened into a tee pad at the start and a green around the
+    // hole that only keeps the `green_undulation`
+    pub fn shape(&self, p: Vec2, height: f32, green_undulation: f32) -> f32 {
+        let tee = 1.0 - smoothstep(self.distance_to_tee(p) / SHAPING_BLEND);
+        let height = height + (self.tee_height - height) * tee;
+
+        let outside_green = (p.distance(self.hole) - GREEN_RADIUS - FRINGE_WIDTH).max(0.0);
+        let green = 1.0 - smoothstep(outside_green / SHAPING_BLEND);
+        height + (self.green_height + green_undulation - height) * green
+    }
+
+    // Start and hole are never under water: close to them `height` is clamped, the clamp falls
+    // away quickly with the distance.
+    pub fn keep_above_water(&self, p: Vec2, height: f32) -> f32 {
+        let distance = p.distance(self.start).min(p.distance(self.hole));
+        height.max(-3.85 - (distance * 0.07).powi(4))
+    }
+
+    // the zone of mown and unmown grass at `p`, hazards have to be checked before
+    pub fn zone_at(&self, course: &CourseLayout, p: Vec2) -> ZoneType {
+        if self.distance_to_tee(p) == 0.0 {
+            ZoneType::Tee
+        } else if p.distance(self.hole) < GREEN_RADIUS {
+            ZoneType::Green
+        } else if p.distance(self.hole) < GREEN_RADIUS + FRINGE_WIDTH {
+            ZoneType::Fringe
+        } else {
+            let closest = course.closest_point(p);
+            match closest.distance - closest.half_width {
+                ..0.0 => ZoneType::Fairway,
+                ..FIRST_CUT_WIDTH => ZoneType::FirstCut,
+                edge if edge < FIRST_CUT_WIDTH + ROUGH_WIDTH => ZoneType::Rough,
+                _ => ZoneType::Offtrack,
+            }
+        }
+    }
+}
+
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub fn bunker_depth(hazards: &[Hazard], p: Vec2) -> f32 {
+    hazards
+        .iter()
+        .filter(|hazard| hazard.kind == HazardKind::Bunker)
+        .map(|bunker| (1.0 - bunker.dis(p.x, p.y)).max(0.0).sqrt() * 1.2)
+        .fold(0.0, f32::max)
+}
+
+pub fn in_waste_area(hazards: &[Hazard], p: Vec2) -> bool {
+    hazards
+        .iter()
+        .filter(|hazard| hazard.kind == HazardKind::WasteArea)
+        .any(|waste_area| waste_area.dis(p.x, p.y) < 1.0)
+}
+
+pub fn hazards_in_square(hazards: &[Hazard], world_offset: [i32; 2], size: f32) -> Vec<Hazard> {
+    hazards
+        .iter()
+        .filter(|hazard| hazard.touches_square(world_offset, size))
+        .cloned()
+        .collect()
+}
+
+pub fn random_side(rng: &mut StdRng) -> f32 {
+    if rng.random_bool(0.5) { 1.0 } else { -1.0 }
+}
+
+// A hazard next to the path with its long side along it, `offset` metres from the edge of the
+// fairway towards `side`. Negative offsets reach into the fairway.
+pub fn beside_path(
+    course: &CourseLayout,
+    kind: HazardKind,
+    arc_length: f32,
+    side: f32,
+    offset: f32,
+    size: f32,
+) -> Hazard {
+    let tangent = course.tangent_at(arc_length);
+    let edge = course.point_at(arc_length)
+        + tangent.perp() * side * (course.half_width_at(arc_length) + offset);
+    Hazard {
+        kind,
+        x: edge.x,
+        y: edge.y,
+        rot: tangent.to_angle(),
+        size,
+    }
+}
+
+// Bunkers wrapping around the back of the green, the front towards the approach stays open.
+pub fn greenside_bunkers(
+    course: &CourseLayout,
+    rng: &mut StdRng,
+    count: u32,
+    sizes: (f32, f32),
+) -> Vec<Hazard> {
+    let hole = course.end();
+    let approach = -course.tangent_at(course.length());
+    let mut angle = random_range(rng, 1.2, 1.6) * random_side(rng);
+    let mut result = Vec::new();
+    for _ in 0..count {
+        let size = random_range(rng, sizes.0, sizes.1);
+        let direction = Vec2::from_angle(angle).rotate(approach);
+        let center = hole + direction * (GREEN_RADIUS + FRINGE_WIDTH + size / 1.6 + 0.5);
+        result.push(Hazard {
+            kind: HazardKind::Bunker,
+            x: center.x,
+            y: center.y,
+            rot: direction.to_angle() + std::f32::consts::FRAC_PI_2,
+            size,
+        });
+        angle += random_range(rng, 0.9, 1.3) * angle.signum();
+    }
+    result
+}
+
+// Keeps the tee pad clear and hazards from piling on top of each other, earlier ones win.
+pub fn clear_of_tee_and_each_other(course: &CourseLayout, hazards: Vec<Hazard>) -> Vec<Hazard> {
+    let start = course.start();
+    let mut placed: Vec<Hazard> = Vec::new();
+    for hazard in hazards {
+        let center = Vec2::new(hazard.x, hazard.y);
+        let near_tee = center.distance(start) < hazard.size + TEE_HALF_LENGTH + SHAPING_BLEND;
+        let overlaps = placed.iter().any(|other| {
+            center.distance(Vec2::new(other.x, other.y)) < (hazard.size + other.size) * 0.7
+        });
+        if !near_tee && !overlaps {
+            placed.push(hazard);
+        }
+    }
+    placed
+}
@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use crate::chunk::CHUNK_SIZE_METERS;
+use crate::generation::registry::{BoxedGenerator, GeneratorRegistry};
+use crate::generation::shaping::{FIRST_CUT_WIDTH, GREEN_RADIUS, ROUGH_WIDTH, TEE_HALF_WIDTH};
+use crate::generation::{PropType, TerrainGenerator, ZoneType};
+
+// metres between the samples along the course line
+const LINE_STEP: f32 = 1.0;
+// Distance beyond the edge of the fairway within which trees count as near the line. No tree
+// grows on the first cut or in the rough, but those standing right behind it hang over it.
+const TREE_CORRIDOR: f32 = FIRST_CUT_WIDTH + ROUGH_WIDTH + 2.0;
+
+// Limits a generated course has to stay within, slopes as rise per run.
+#[derive(Resource, Clone)]
+pub struct CourseThresholds {
+    pub max_tee_slope: f32,
+    pub max_green_slope: f32,
+    // metres of the course line under water
+    pub max_water_on_line: f32,
+    pub max_trees_near_line: usize,
+    // seeds tried before the last course is played anyway, and the time they may take
+    pub max_rerolls: u32,
+    pub max_reroll_time: Duration,
+}
+
+impl Default for CourseThresholds {
+    fn default() -> Self {
+        CourseThresholds {
+            max_tee_slope: 0.1,
+            max_green_slope: 0.1,
+            max_water_on_line: 150.0,
+            max_trees_near_line: 2,
+            max_rerolls: 20,
+            max_reroll_time: Duration::from_millis(500),
+        }
+    }
+}
+
+// What playing the course would be like, measured along the course line.
+pub struct CourseReport {
+    pub tee_zone: ZoneType,
+    pub green_zone: ZoneType,
+    pub tee_slope: f32,
+    pub green_slope: f32,
+    pub water_on_line: f32,
+    // longest stretch of water along the course line
+    pub widest_carry: f32,
+    // trees within `TREE_CORRIDOR` of the fairway
+    pub trees_near_line: usize,
+}
+
+impl CourseReport {
+    // `max_carry` is the `GeneratorOptions::max_carry` the course was built with
+    pub fn problems(&self, thresholds: &CourseThresholds, max_carry: f32) -> Vec<String> {
+        let mut result = Vec::new();
+        if self.tee_zone != ZoneType::Tee {
+            result.push(format!("tee lies in {}", self.tee_zone.label()));
+        }
+        if self.green_zone != ZoneType::Green {
+            result.push(format!("hole lies in {}", self.green_zone.label()));
+        }
+        if self.tee_slope > thresholds.max_tee_slope {
+            result.push(format!("tee slope {:.2}", self.tee_slope));
+        }
+        if self.green_slope > thresholds.max_green_slope {
+            result.push(format!("green slope {:.2}", self.green_slope));
+        }
+        if self.water_on_line > thresholds.max_water_on_line {
+            result.push(format!("{:.0}m of water on the line", self.water_on_line));
+        }
+        if self.widest_carry > max_carry {
+            result.push(format!("{:.0}m carry", self.widest_carry));
+        }
+        if self.trees_near_line > thresholds.max_trees_near_line {
+            result.push(format!("{} trees lining the rough", self.trees_near_line));
+        }
+        result
+    }
+
+    pub fn passes(&self, thresholds: &CourseThresholds, max_carry: f32) -> bool {
+        self.problems(thresholds, max_carry).is_empty()
+    }
+}
+
+pub fn validate_course(generator: &dyn TerrainGenerator) -> CourseReport {
+    let course = generator.course_layout();
+    let start = course.start();
+    let hole = course.end();
+
+    let mut water_on_line = 0.0;
+    let mut widest_carry: f32 = 0.0;
+    let mut carry = 0.0;
+    for step in 0..=(course.length() / LINE_STEP) as usize {
+        let p = course.point_at(step as f32 * LINE_STEP);
+        if generator.zone_type_at(p.x, p.y) == ZoneType::DeadZone {
+            water_on_line += LINE_STEP;
+            carry += LINE_STEP;
+            widest_carry = widest_carry.max(carry);
+        } else {
+            carry = 0.0;
+        }
+    }
+
+    CourseReport {
+        tee_zone: generator.zone_type_at(start.x, start.y),
+        green_zone: generator.zone_type_at(hole.x, hole.y),
+        // measured across the whole pad and green, so small undulations do not count
+        tee_slope: slope_at(generator, start, TEE_HALF_WIDTH),
+        green_slope: slope_at(generator, hole, GREEN_RADIUS * 0.5),
+        water_on_line,
+        widest_carry,
+        trees_near_line: trees_near_line(generator),
+    }
+}
+
+// Builds courses with the selected generator, rerolling the seed until one passes. Returns it
+// with the `GeneratorRegistry::variant` it was built from.
+pub fn create_playable(
+    registry: &mut GeneratorRegistry,
+    thresholds: &CourseThresholds,
+    seed: u32,
+) -> (BoxedGenerator, String) {
+    let index = registry.pick(seed);
+    let generator = reroll(
+        registry.builder(index),
+        thresholds,
+        registry.options.max_carry(),
+        seed,
+    );
+    (generator, registry.variant(index))
+}
+
+// A course built by `create_playable` on the `AsyncComputeTaskPool`, replaces the course of
+// the `ChunkManager` once done.
+#[derive(Resource)]
+pub struct PlayableCourse(pub Task<(BoxedGenerator, String)>);
+
+impl PlayableCourse {
+    pub fn spawn(
+        registry: &mut GeneratorRegistry,
+        thresholds: &CourseThresholds,
+        seed: u32,
+    ) -> Self {
+        let index = registry.pick(seed);
+        let build = registry.builder(index);
+        let thresholds = thresholds.clone();
+        let max_carry = registry.options.max_carry();
+        let variant = registry.variant(index);
+        PlayableCourse(
+            AsyncComputeTaskPool::get()
+                .spawn(async move { (reroll(build, &thresholds, max_carry, seed), variant) }),
+        )
+    }
+}
+
+fn reroll(
+    build: impl Fn(u32) -> BoxedGenerator,
+    thresholds: &CourseThresholds,
+    max_carry: f32,
+    seed: u32,
+) -> BoxedGenerator {
+    let started = Instant::now();
+    let mut seed = seed;
+    let mut generator = build(seed);
+    for _ in 0..thresholds.max_rerolls {
+        let problems = validate_course(generator.as_ref()).problems(thresholds, max_carry);
+        if problems.is_empty() || started.elapsed() > thresholds.max_reroll_time {
+            break;
+        }
+        info!("rerolling course {seed}: {}", problems.join(", "));
+        seed = seed.wrapping_add(1);
+        generator = build(seed);
+    }
+    generator
+}
+
+fn slope_at(generator: &dyn TerrainGenerator, p: Vec2, d: f32) -> f32 {
+    let dx = generator.height_at(p.x + d, p.y) - generator.height_at(p.x - d, p.y);
+    let dz = generator.height_at(p.x, p.y + d) - generator.height_at(p.x, p.y - d);
+    Vec2::new(dx, dz).length() / (2.0 * d)
+}
+
+fn trees_near_line(generator: &dyn TerrainGenerator) -> usize {
+    let course = generator.course_layout();
+
+    // every chunk the corridor reaches into
+    let mut chunks = HashSet::new();
+    for step in 0..=(course.length() / LINE_STEP) as usize {
+        let arc_length = step as f32 * LINE_STEP;
+        let center = course.point_at(arc_length);
+        let side = course.tangent_at(arc_length).perp();
+        let reach = course.half_width_at(arc_length) + TREE_CORRIDOR;
+        for across in [-1.0, -0.5, 0.0, 0.5, 1.0] {
+            let p = center + side * reach * across;
+            chunks.insert((
+                (p.x / CHUNK_SIZE_METERS as f32).floor() as i32,
+                (p.y / CHUNK_SIZE_METERS as f32).floor() as i32,
+            ));
+        }
+    }
+
+    chunks
+        .into_iter()
+        .flat_map(|(x, z)| {
+            let size = CHUNK_SIZE_METERS as i32;
+            let offset = (x * size, z * size);
+            generator
+                .props_in_chunk(offset)
+                .into_iter()
+                .map(move |prop| (offset, prop))
+        })
+        .filter(|(offset, prop)| {
+            let position = Vec2::new(
+                offset.0 as f32 + prop.position.0,
+                offset.1 as f32 + prop.position.2,
+            );
+            let closest = course.closest_point(position);
+            matches!(prop.prop_type, PropType::Tree)
+                && closest.distance < closest.half_width + TREE_CORRIDOR
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Hazard;
+    use crate::generation::Prop;
+    use crate::generation::course_layout::CourseLayout;
+    use crate::generation::grasslands::GrasslandsGenerator;
+
+    // a course of `GrasslandsGenerator` with one more tree at `tree`
+    struct PlantedTree {
+        course: GrasslandsGenerator,
+        tree: Vec2,
+    }
+
+    impl TerrainGenerator for PlantedTree {
+        fn seed(&self) -> u32 {
+            self.course.seed()
+        }
+        fn height_at(&self, x: f32, y: f32) -> f32 {
+            self.course.height_at(x, y)
+        }
+        fn props_in_chunk(&self, offset: (i32, i32)) -> Vec<Prop> {
+            let mut result = self.course.props_in_chunk(offset);
+            let position = self.tree - Vec2::new(offset.0 as f32, offset.1 as f32);
+            if (0.0..CHUNK_SIZE_METERS as f32).contains(&position.x)
+                && (0.0..CHUNK_SIZE_METERS as f32).contains(&position.y)
+            {
+                result.push(Prop {
+                    prop_type: PropType::Tree,
+                    position: (position.x, 0.0, position.y),
+                    seed: 0,
+                });
+            }
+            result
+        }
+        fn course_layout(&self) -> &CourseLayout {
+            self.course.course_layout()
+        }
+        fn start(&self) -> [f32; 2] {
+            self.course.start()
+        }
+        fn hole(&self) -> [f32; 2] {
+            self.course.hole()
+        }
+        fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
+            self.course.zone_type_at(x, y)
+        }
+        fn hazards_in_chunk(&self, world_offset: [i32; 2]) -> Vec<Hazard> {
+            self.course.hazards_in_chunk(world_offset)
+        }
+        fn hazards(&self) -> &[Hazard] {
+            self.course.hazards()
+        }
+    }
+
+    // the tree stands just behind the rough, half way along the course
+    fn planted(seed: u32) -> PlantedTree {
+        let course = GrasslandsGenerator::new(seed);
+        let layout = course.course_layout();
+        let arc_length = layout.length() * 0.5;
+        let beyond_rough = layout.half_width_at(arc_length) + FIRST_CUT_WIDTH + ROUGH_WIDTH + 1.0;
+        let tree =
+            layout.point_at(arc_length) + layout.tangent_at(arc_length).perp() * beyond_rough;
+        PlantedTree { course, tree }
+    }
+
+    #[test]
+    fn counts_a_tree_right_behind_the_rough() {
+        let plain = validate_course(&GrasslandsGenerator::new(7));
+        let report = validate_course(&planted(7));
+        assert_eq!(report.trees_near_line, plain.trees_near_line + 1);
+    }
+
+    #[test]
+    fn rejects_a_course_with_too_many_trees_near_the_line() {
+        let thresholds = CourseThresholds {
+            max_trees_near_line: validate_course(&GrasslandsGenerator::new(7)).trees_near_line,
+            ..default()
+        };
+        let report = validate_course(&planted(7));
+        let problems = report.problems(&thresholds, f32::INFINITY);
+        assert!(problems.iter().any(|problem| problem.contains("trees")));
+    }
+}
@@ -2,18 +2,26 @@ mod add_chunk_collider;
 mod animation;
 mod camera;
 pub mod chunk;
+mod club;
 pub mod generation;
 mod material;
 mod objects;
+mod physics;
+mod rating;
+mod rules;
+mod scorecard;
 mod state;
 mod ui;
 
 use crate::material::CustomMaterialsPlugin;
 use crate::{
-    add_chunk_collider::create_collider_from_mesh,
     camera::CameraPlugin,
     chunk::ChunkPlugin,
-    objects::{flag_pole::FlagPolePlugin, golfball::GolfballPlugin},
+    club::ClubPlugin,
+    objects::{cup::CupPlugin, flag_pole::FlagPolePlugin, golfball::GolfballPlugin},
+    physics::BallPhysicsPlugin,
+    rules::RulesPlugin,
+    scorecard::ScorecardPlugin,
     state::{
         aim::AimStatePlugin,
         inshot::InShotPlugin,
@@ -45,10 +53,12 @@ fn main() {
             PresentCoursePlugin,
             FlagPolePlugin,
             CustomMaterialsPlugin,
+            ClubPlugin,
+            BallPhysicsPlugin,
         ))
+        .add_plugins((ScorecardPlugin, RulesPlugin, CupPlugin))
         .init_state::<AppState>()
         .add_systems(Startup, setup)
-        .add_systems(Update, create_collider_from_mesh)
         .add_systems(Update, debug_state_change_input_handler) // change game states for debug
         .run();
 }
@@ -72,7 +82,7 @@ fn setup(mut commands: Commands) {
 
     // Text to describe the controls.
     commands.spawn((
-        Text::new("Controls:\nArrow Keys: Rotate\nSpace: Shoot\n"),
+        Text::new("Controls:\nArrow Keys: Rotate\nQ/E: Change Club\nTab: Change Drop\nSpace: Shoot\nC: Course Menu\n"),
         Node {
             position_type: PositionType::Absolute,
             top: px(200),
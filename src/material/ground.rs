@@ -1,84 +1,69 @@
+use crate::chunk::{Hazard, HazardUniform};
+use crate::generation::TerrainGenerator;
+use crate::generation::course_layout::CourseLayoutUniform;
 use bevy::pbr::MaterialExtension;
 use bevy::prelude::*;
 use bevy::render::render_resource::{AsBindGroup, ShaderType};
+use bevy::render::storage::ShaderStorageBuffer;
 use bevy::shader::ShaderRef;
-use crate::chunk::Bunker;
 
 const SHADER_ASSET_PATH: &str = "shaders/ground_material.wgsl";
 
-// TODO: rename to something like color golf course
-
-const COURSE_WIDTH: f32 = 25.0;
-
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default, ShaderType)]
-pub struct Polynomial {
-    pub a: f32,
-    pub b: f32,
-    pub c: f32,
-    pub d: f32,
-}
-
-impl Polynomial {
-    pub fn f(&self, x: f32) -> f32 {
-        self.a * x * x * x + self.b * x * x + self.c * x + self.d
-    }
-
-    pub fn f_prime(&self, x: f32) -> f32 {
-        3.0 * self.a * x * x + 2.0 * self.b * x + self.c
-    }
-
-    pub fn approx_distance_to_curve(&self, p: [f32; 2]) -> f32 {
-        let p_y = self.f(p[0]);
-        let p_d = self.f_prime(p[0]);
-        let h = (1.0 + p_d * p_d).sqrt();
-        ((p_y - p[1]) / h).abs()
-    }
-
-    pub fn on_clean_grass(&self, p: [f32; 2]) -> bool {
-        match p[0] {
-            ..0.0 => {
-                let p_d = self.f_prime(0.0);
-                let h = (1.0 + p_d * p_d).sqrt();
-                let dy = self.f(0.0) - (p[1] - p[0] * p_d);
-                (dy * dy + p[0] * p[0]).sqrt() < COURSE_WIDTH * h
-            },
-            300.0.. => {
-                let p_d = self.f_prime(300.0);
-                let h = (1.0 + p_d * p_d).sqrt();
-                let dx = p[0] - 300.0;
-                let dy = self.f(300.0) - (p[1] - (p[0] - 300.0) * p_d);
-                (dy * dy + dx * dx).sqrt() < COURSE_WIDTH * h
-            },
-            _ => {
-                self.approx_distance_to_curve(p) < COURSE_WIDTH
-            },
-        }
-    }
-}
-
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
 pub struct GroundMaterial {
     #[uniform(100)]
-    course: Polynomial,
-    #[uniform(100)]
-    bunker: Bunker,
+    course: CourseLayoutUniform,
     #[uniform(100)]
-    start_x: f32,
-    #[uniform(100)]
-    end_x: f32,
+    style: GroundStyle,
+    // every hazard touching the chunk, see `hazard_buffer`
+    #[storage(101, read_only, visibility(fragment))]
+    hazards: Handle<ShaderStorageBuffer>,
 }
 
 impl GroundMaterial {
-    pub fn new(course: Polynomial, bunker: Bunker) -> Self {
+    pub fn new(
+        course: CourseLayoutUniform,
+        style: GroundStyle,
+        hazards: Handle<ShaderStorageBuffer>,
+    ) -> Self {
         GroundMaterial {
             course,
-            start_x: 0.0,
-            end_x: 300.0,
-            bunker,
+            style,
+            hazards,
         }
     }
 }
 
+// How the biome of the course colours the ground.
+#[derive(Reflect, Debug, Clone, Copy, Default, ShaderType)]
+pub struct GroundStyle {
+    // multiplied onto the colour of the grass
+    tint: Vec4,
+    // outside the course, ground above this height is snow and steeper ground is rock
+    snow_line: f32,
+    rock_slope: f32,
+}
+
+impl GroundStyle {
+    pub fn of(generator: &dyn TerrainGenerator) -> Self {
+        GroundStyle {
+            tint: generator.ground_tint().extend(1.0),
+            snow_line: generator.snow_line(),
+            rock_slope: generator.rock_slope(),
+        }
+    }
+}
+
+// Storage buffers may not be empty, chunks without hazards get a single one of size 0 that
+// the shader skips.
+pub fn hazard_buffer(hazards: &[Hazard]) -> ShaderStorageBuffer {
+    let mut uniforms: Vec<HazardUniform> = hazards.iter().map(Hazard::uniform).collect();
+    if uniforms.is_empty() {
+        uniforms.push(HazardUniform::default());
+    }
+    ShaderStorageBuffer::from(uniforms)
+}
+
 impl MaterialExtension for GroundMaterial {
     fn fragment_shader() -> ShaderRef {
         SHADER_ASSET_PATH.into()
@@ -87,4 +72,4 @@ impl MaterialExtension for GroundMaterial {
     fn deferred_fragment_shader() -> ShaderRef {
         SHADER_ASSET_PATH.into()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,154 @@
+use avian3d::prelude::{Collider, CollidingEntities, LinearVelocity, Sensor};
+use bevy::prelude::*;
+
+use crate::{
+    chunk::cup::{CUP_DEPTH, CUP_RADIUS, CupGeometry},
+    objects::golfball::Golfball,
+    physics::flight::BALL_RADIUS,
+    state::state::AppState,
+};
+
+// m/s, a faster ball catches the back of the cup and pops out again
+const MAX_HOLING_SPEED: f32 = 1.3;
+// vertical speed that lifts a lipped out ball over the rim
+const LIP_OUT_POP: f32 = 1.8;
+// fraction of the horizontal speed kept when lipping out
+const LIP_OUT_KEEP: f32 = 0.6;
+
+pub struct CupPlugin;
+impl Plugin for CupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, detect_holed_ball.run_if(in_state(AppState::InShot)));
+    }
+}
+
+// Sits in the cup below the rim, the ball only touches it once it dropped in.
+#[derive(Component)]
+pub struct CupSensor;
+
+// The rim and walls of the cup and its sensor, under the static body of the chunk collider.
+// The geometry is in world space, `transform` undoes the transform of the parent.
+pub fn spawn_cup_colliders(
+    builder: &mut ChildSpawnerCommands,
+    cup: &CupGeometry,
+    transform: Transform,
+) {
+    let (vertices, indices) = cup.collider_geometry();
+    let sensor_height = CUP_DEPTH - 2.0 * BALL_RADIUS;
+    builder
+        .spawn((transform, Collider::trimesh(vertices, indices)))
+        .with_children(|builder| {
+            builder.spawn((
+                CupSensor,
+                Sensor,
+                Collider::cylinder(CUP_RADIUS, sensor_height),
+                CollidingEntities::default(),
+                Transform::from_translation(cup.bottom + Vec3::Y * sensor_height * 0.5),
+            ));
+        });
+}
+
+fn detect_holed_ball(
+    sensors: Query<&CollidingEntities, With<CupSensor>>,
+    golfball: Single<(Entity, &mut LinearVelocity), With<Golfball>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let (entity, mut velocity) = golfball.into_inner();
+    if !sensors.iter().any(|colliding| colliding.contains(&entity)) {
+        return;
+    }
+
+    if velocity.length() <= MAX_HOLING_SPEED {
+        app_state.set(AppState::PostScore);
+    } else if velocity.y <= 0.0 {
+        info!("lipped out at {:.1}m/s", velocity.length());
+        let horizontal = Vec3::new(velocity.x, 0.0, velocity.z);
+        velocity.0 = horizontal * LIP_OUT_KEEP + Vec3::Y * LIP_OUT_POP;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use avian3d::prelude::{ColliderDisabled, PhysicsPlugins, RigidBody};
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::generation::TerrainGenerator;
+    use crate::generation::grasslands::GrasslandsGenerator;
+    use crate::objects::flag_pole::{pin_collider, pole_transform};
+
+    // Drops the ball at `offset` from the centre of the cup, `height` above the rim, and
+    // whether it ends up on the sensor within two seconds.
+    fn drop_ball(offset: Vec2, height: f32, pin_out: bool) -> bool {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            bevy::mesh::MeshPlugin,
+            bevy::scene::ScenePlugin,
+            PhysicsPlugins::default(),
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 64.0,
+        )));
+
+        // what `App::run` does before the first update
+        app.finish();
+        app.cleanup();
+
+        let generator = GrasslandsGenerator::new(7);
+        let cup = CupGeometry::generate(&generator);
+        let [x, z] = generator.hole();
+        let hole = Vec3::new(x, generator.height_at(x, z), z);
+
+        let mut commands = app.world_mut().commands();
+        commands
+            .spawn((RigidBody::Static, Transform::default()))
+            .with_children(|builder| spawn_cup_colliders(builder, &cup, Transform::default()));
+        commands
+            .spawn((RigidBody::Static, pole_transform(hole)))
+            .with_children(|builder| {
+                let mut pin = builder.spawn(pin_collider());
+                if pin_out {
+                    pin.insert(ColliderDisabled);
+                }
+            });
+        let ball = commands
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::sphere(BALL_RADIUS),
+                Transform::from_translation(
+                    hole + Vec3::new(offset.x, BALL_RADIUS + height, offset.y),
+                ),
+            ))
+            .id();
+        app.world_mut().flush();
+
+        for _ in 0..128 {
+            app.update();
+            let mut sensors = app
+                .world_mut()
+                .query_filtered::<&CollidingEntities, With<CupSensor>>();
+            if sensors
+                .iter(app.world())
+                .any(|colliding| colliding.contains(&ball))
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn ball_dropped_over_the_centre_is_holed() {
+        assert!(drop_ball(Vec2::ZERO, 0.3, true));
+    }
+
+    #[test]
+    fn ball_dropped_next_to_the_pin_falls_past_it() {
+        assert!(drop_ball(Vec2::new(0.03, 0.0), 0.02, false));
+    }
+}
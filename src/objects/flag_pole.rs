@@ -1,4 +1,4 @@
-use avian3d::prelude::RigidBody;
+use avian3d::prelude::{Collider, RigidBody};
 use bevy::{
     asset::RenderAssetUsages,
     color::palettes::css::{BLUE, RED, WHITE},
@@ -9,6 +9,7 @@ use bevy::{
     shader::ShaderRef,
 };
 use crate::chunk::chunk_manager::ChunkManager;
+use crate::physics::wind::Wind;
 
 pub struct FlagPolePlugin;
 
@@ -17,13 +18,39 @@ impl Plugin for FlagPolePlugin {
         app.add_plugins(MaterialPlugin::<
             ExtendedMaterial<StandardMaterial, FlagMaterialExtension>,
         >::default())
-            .add_systems(PostStartup, spawn_flag_pole);
+            .add_systems(PostStartup, spawn_flag_pole)
+            .add_systems(Update, update_flag_wind);
     }
 }
 
 #[derive(Component)]
 pub struct FlagPole;
 
+// The pin the flag hangs from. Its collider is disabled while putting, like a flagstick taken
+// out of the hole.
+#[derive(Component)]
+pub struct Pin;
+
+// half an inch across, a ball hitting it still fits between it and the wall of the cup
+const PIN_RADIUS: f32 = 0.00635;
+const PIN_HEIGHT: f32 = 2.0;
+// the flag pole entity stands this far above the hole
+const POLE_LIFT: f32 = 0.5;
+
+// where the flag pole stands over the cup at `hole`
+pub(crate) fn pole_transform(hole: Vec3) -> Transform {
+    Transform::from_translation(hole + Vec3::Y * POLE_LIFT)
+}
+
+// the collider of the pin relative to the flag pole, from the rim of the cup upwards
+pub(crate) fn pin_collider() -> (Pin, Collider, Transform) {
+    (
+        Pin,
+        Collider::cylinder(PIN_RADIUS, PIN_HEIGHT),
+        Transform::from_xyz(0.0, PIN_HEIGHT * 0.5 - POLE_LIFT, 0.0),
+    )
+}
+
 fn spawn_flag_pole(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -32,22 +59,23 @@ fn spawn_flag_pole(
     chunk_manager: Res<ChunkManager>,
 ) {
     let [x, z] = chunk_manager.generator.hole();
-    let y = chunk_manager.generator.height_at(x, z) + 0.5;
+    let y = chunk_manager.generator.height_at(x, z);
 
     commands
         .spawn((
             FlagPole,
             RigidBody::Static,
-            Transform::from_xyz(x, y, z),
+            pole_transform(Vec3::new(x, y, z)),
         ))
         .with_children(|builder| {
+            // the pin stands on the rim of the cup and deflects balls hitting it
             builder.spawn((
-                Mesh3d(meshes.add(Cylinder::new(0.05, 2.0))),
+                Mesh3d(meshes.add(Cylinder::new(PIN_RADIUS, PIN_HEIGHT))),
                 MeshMaterial3d(default_materials.add(StandardMaterial {
                     base_color: Color::from(WHITE),
                     ..Default::default()
                 })),
-                Transform::from_xyz(0.0, 0.0, 0.0),
+                pin_collider(),
             ));
             builder.spawn((
                 Mesh3d(meshes.add(generate_mesh())),
@@ -58,13 +86,45 @@ fn spawn_flag_pole(
                     },
                     extension: FlagMaterialExtension {
                         color: Color::from(BLUE).to_linear(),
+                        wind_strength: 0.0,
                     },
                 })),
-                Transform::from_xyz(0.5, 0.75, 0.0).with_scale(Vec3::new(1.0, 0.5, 1.0)),
+                Transform::from_xyz(0.5, 1.25, 0.0).with_scale(Vec3::new(1.0, 0.5, 1.0)),
             ));
         });
 }
 
+// wind speed (m/s) at which the cloth flaps the hardest
+const FULL_FLAP_WIND_SPEED: f32 = 10.0;
+
+fn update_flag_wind(
+    wind: Res<Wind>,
+    mut flag_pole: Query<&mut Transform, With<FlagPole>>,
+    mut materials: ResMut<Assets<ExtendedMaterial<StandardMaterial, FlagMaterialExtension>>>,
+) {
+    let velocity = wind.velocity();
+    // the cloth hangs off the pole along the local x axis, point it downwind
+    if velocity.xz().length() > f32::EPSILON {
+        for mut transform in &mut flag_pole {
+            transform.rotation = Quat::from_rotation_y((-velocity.z).atan2(velocity.x));
+        }
+    }
+
+    // writing a material re-uploads it, so only those with another value are touched, and the
+    // strength moves in steps of 1 % so a slowly drifting gust doesn't change it every frame
+    let wind_strength = ((wind.speed() / FULL_FLAP_WIND_SPEED).min(1.5) * 100.0).round() / 100.0;
+    let outdated = materials
+        .iter()
+        .filter(|(_, material)| material.extension.wind_strength != wind_strength)
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    for id in outdated {
+        if let Some(material) = materials.get_mut(id) {
+            material.extension.wind_strength = wind_strength;
+        }
+    }
+}
+
 fn generate_mesh() -> Mesh {
     let mut result = Mesh::new(
         PrimitiveTopology::TriangleList,
@@ -124,6 +184,8 @@ const PREPASS_SHADER_ASSET_PATH: &str = "shaders/flag_pole_prepass.wgsl";
 struct FlagMaterialExtension {
     #[uniform(100)]
     color: LinearRgba,
+    #[uniform(101)]
+    wind_strength: f32,
 }
 
 impl MaterialExtension for FlagMaterialExtension {
@@ -1,15 +1,18 @@
 use std::time::Duration;
 
-use crate::chunk::chunk_loader::ChunkLoader;
-use crate::chunk::chunk_manager::ChunkManager;
-use crate::objects::flag_pole::FlagPole;
-use crate::{camera::ActiveCamera, state::state::AppState};
+use crate::{
+    camera::ActiveCamera,
+    chunk::{chunk_loader::ChunkLoader, chunk_manager::ChunkManager},
+    physics::flight::{BALL_RADIUS, BallFlight},
+    rules::{BallTrack, OutOfBounds, rule_ball_at_rest},
+    scorecard::Scorecard,
+    state::state::AppState,
+};
 use avian3d::prelude::{
-    AngularDamping, AngularInertia, CoefficientCombine, Collider, Friction, LinearDamping,
-    LinearVelocity, Mass, Restitution, RigidBody,
+    AngularDamping, AngularInertia, CoefficientCombine, Collider, CollidingEntities, Friction,
+    LinearVelocity, Mass, Restitution, RigidBody, SweptCcd,
 };
 use bevy::{color::palettes::css::WHITE, prelude::*};
-use crate::generation::ZoneType;
 
 pub struct GolfballPlugin;
 impl Plugin for GolfballPlugin {
@@ -17,16 +20,11 @@ impl Plugin for GolfballPlugin {
         app.add_systems(Startup, spawn_golfball)
             .add_systems(
                 Update,
-                (
-                    input_handler,
-                    input_handler_golfball,
-                    update_rigid_mode,
-                ),
+                (input_handler, input_handler_golfball, update_rigid_mode),
             )
             .add_systems(
                 Update,
-                (check_ball_moving_system, regenerate_after_hitting_hole)
-                    .run_if(in_state(AppState::InShot)),
+                check_ball_moving_system.run_if(in_state(AppState::InShot)),
             )
             .add_systems(OnEnter(AppState::InShot), set_ball_active)
             .add_systems(OnExit(AppState::InShot), set_ball_inactive);
@@ -38,9 +36,10 @@ pub struct Golfball {
     active: bool,
 }
 
+// where the last shot was played from, stroke and distance puts the ball back here
 #[derive(Component)]
-struct PrevPosition {
-    xyz: Vec3,
+pub(crate) struct PrevPosition {
+    pub xyz: Vec3,
 }
 
 impl PrevPosition {
@@ -54,7 +53,7 @@ fn spawn_golfball(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let radius = 0.021335;
+    let radius = BALL_RADIUS;
     commands.spawn((
         Golfball { active: true },
         Transform::from_xyz(0.0, 10.0, 0.0),
@@ -63,18 +62,20 @@ fn spawn_golfball(
         MeshMaterial3d(materials.add(Color::from(WHITE))),
         ChunkLoader::new(32.0),
         RigidBody::Dynamic,
-        Collider::sphere(radius),
+        // a driven ball moves several times its size per physics step
+        (Collider::sphere(radius), SweptCcd::default()),
         Mass(0.005),
         LinearVelocity::default(),
         AngularInertia::new(Vec3::splat(0.9)),
         AngularDamping(2.5),
-        LinearDamping(0.01), // air resistance
+        (BallFlight::default(), CollidingEntities::default()), // air resistance, lift and spin
         Friction {
             static_coefficient: 0.5,
             dynamic_coefficient: 1.0,
             combine_rule: CoefficientCombine::Average,
-        }, // friction todo: dependent on ground
-        Restitution::new(0.2),
+        },
+        // updated from the zone under the ball, rolling resistance is applied separately
+        Restitution::new(0.2).with_combine_rule(CoefficientCombine::Max),
     ));
 }
 
@@ -130,42 +131,42 @@ fn set_ball_inactive(mut golfball: Single<&mut Golfball>) {
     golfball.active = false
 }
 
-fn regenerate_after_hitting_hole(
-    golfball: Single<&mut Transform, (With<Golfball>, Without<FlagPole>)>,
-    chunk_manager: ResMut<ChunkManager>,
-    mut app_state: ResMut<NextState<AppState>>,
-) {
-    let [hole_x, hole_z] = chunk_manager.generator.hole();
-    let hole_y = chunk_manager.generator.height_at(hole_x, hole_z);
-
-    let on_hole = (golfball.translation.x - hole_x).abs() <= 0.5
-        && (golfball.translation.y - hole_y).abs() <= 0.2
-        && (golfball.translation.z - hole_z).abs() <= 0.5;
-
-    if on_hole {
-        app_state.set(AppState::PostScore);
-    }
-}
-
 fn check_ball_moving_system(
     mut golfball: Single<(&LinearVelocity, &mut Transform, &mut PrevPosition), With<Golfball>>,
     time: Res<Time>,
     mut duration: Local<Duration>,
     mut game_state: ResMut<NextState<AppState>>,
     chunk_manager: Res<ChunkManager>,
+    out_of_bounds: Res<OutOfBounds>,
+    track: Res<BallTrack>,
+    mut scorecard: ResMut<Scorecard>,
+    mut commands: Commands,
 ) {
     let velocity = golfball.0.0.length();
     if velocity < 0.1 {
         *duration += time.delta();
     }
     if *duration > Duration::from_secs(2) {
-        // reset if in water or store current position if not
-        let x = golfball.1.translation.x;
-        let z = golfball.1.translation.z;
-        if chunk_manager.generator.zone_type_at(x, z) == ZoneType::DeadZone {
-            golfball.1.translation = golfball.2.xyz;
-        } else {
-            golfball.2.xyz = golfball.1.translation;
+        // penalty drop if in water or out of bounds, store current position if not
+        match rule_ball_at_rest(
+            chunk_manager.generator.as_ref(),
+            &out_of_bounds,
+            &track,
+            golfball.1.translation,
+            golfball.2.xyz,
+        ) {
+            Some(penalty_drop) => {
+                info!(
+                    "{}, {} stroke penalty",
+                    penalty_drop.reason.label(),
+                    penalty_drop.strokes()
+                );
+                scorecard.add_penalty(penalty_drop.strokes());
+                golfball.1.translation = penalty_drop.selected().position;
+                golfball.2.xyz = golfball.1.translation;
+                commands.insert_resource(penalty_drop);
+            }
+            None => golfball.2.xyz = golfball.1.translation,
         }
 
         *duration = Duration::ZERO;
@@ -1,4 +1,5 @@
 pub mod aim_tiksu;
+pub mod cup;
 pub mod flag_pole;
 pub mod golfball;
 pub mod win_tiksu;
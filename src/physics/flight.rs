@@ -0,0 +1,169 @@
+use avian3d::prelude::{CollidingEntities, Forces, RigidBody, RigidBodyDisabled, RigidBodyForces};
+use bevy::prelude::*;
+
+use crate::chunk::chunk_manager::ChunkManager;
+use crate::physics::launch::GRAVITY;
+use crate::physics::surface::surface_profile;
+use crate::physics::wind::Wind;
+
+const AIR_DENSITY: f32 = 1.225;
+pub const BALL_RADIUS: f32 = 0.021335;
+// regulation ball mass, the collider uses its own mass for contacts
+const BALL_MASS: f32 = 0.04593;
+const DRAG_COEFFICIENT: f32 = 0.22;
+// lift coefficient per unit of spin ratio (surface speed / ball speed)
+const LIFT_PER_SPIN_RATIO: f32 = 1.5;
+const MAX_LIFT_COEFFICIENT: f32 = 0.35;
+// fraction of the spin lost per second in the air
+const SPIN_DECAY: f32 = 0.04;
+// backspin (rad/s) below which the ball does not check up on landing
+const CHECK_UP_MIN_SPIN: f32 = 200.0;
+// backspin (rad/s) above the minimum that removes all horizontal speed on landing
+const CHECK_UP_SPIN_RANGE: f32 = 1000.0;
+const MAX_CHECK_UP: f32 = 0.75;
+
+// Aerodynamic state of the ball. The spin is tracked separately from avian's angular
+// velocity, which is only used for the ball rolling on the ground.
+#[derive(Component, Default)]
+pub struct BallFlight {
+    // rad/s in world space
+    pub spin: Vec3,
+    in_flight: bool,
+}
+
+impl BallFlight {
+    pub fn launch(&mut self, spin: Vec3) {
+        self.spin = spin;
+        self.in_flight = false;
+    }
+}
+
+// Quadratic drag plus spin dependent (Magnus) lift, `velocity` is relative to the air.
+pub fn aerodynamic_acceleration(velocity: Vec3, spin: Vec3) -> Vec3 {
+    let speed = velocity.length();
+    if speed < f32::EPSILON {
+        return Vec3::ZERO;
+    }
+    let area = std::f32::consts::PI * BALL_RADIUS * BALL_RADIUS;
+    let dynamic_pressure = 0.5 * AIR_DENSITY * area * speed * speed / BALL_MASS;
+
+    let drag = -velocity / speed * DRAG_COEFFICIENT * dynamic_pressure;
+
+    let spin_rate = spin.length();
+    let lift = if spin_rate < f32::EPSILON {
+        Vec3::ZERO
+    } else {
+        let spin_ratio = BALL_RADIUS * spin_rate / speed;
+        let lift_coefficient = (spin_ratio * LIFT_PER_SPIN_RATIO).min(MAX_LIFT_COEFFICIENT);
+        spin.cross(velocity).normalize_or_zero() * lift_coefficient * dynamic_pressure
+    };
+
+    drag + lift
+}
+
+// Step-by-step integration of the same flight model the ball uses, for predictions.
+pub struct FlightSimulation {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub spin: Vec3,
+    pub wind: Vec3,
+}
+
+impl FlightSimulation {
+    pub fn new(position: Vec3, velocity: Vec3, spin: Vec3, wind: Vec3) -> Self {
+        FlightSimulation {
+            position,
+            velocity,
+            spin,
+            wind,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        let acceleration =
+            aerodynamic_acceleration(self.velocity - self.wind, self.spin) - Vec3::Y * GRAVITY;
+        self.velocity += acceleration * dt;
+        self.position += self.velocity * dt;
+        self.spin *= 1.0 - SPIN_DECAY * dt;
+    }
+
+    // Steps until the ball comes down onto the ground given by `ground_height` or `max_time`
+    // runs out. Returns every position on the way, the last one on the ground.
+    pub fn trace(
+        &mut self,
+        dt: f32,
+        max_time: f32,
+        ground_height: impl Fn(f32, f32) -> f32,
+    ) -> Vec<Vec3> {
+        let mut path = vec![self.position];
+        let mut time = 0.0;
+        while time < max_time {
+            let previous = self.position;
+            self.step(dt);
+            time += dt;
+
+            let ground = ground_height(self.position.x, self.position.z);
+            if self.velocity.y < 0.0 && self.position.y <= ground {
+                // interpolate to where the step went through the ground
+                let above = previous.y - ground_height(previous.x, previous.z);
+                let below = ground - self.position.y;
+                let t = above / (above + below).max(f32::EPSILON);
+                let landing = previous.lerp(self.position, t);
+                path.push(landing.with_y(ground_height(landing.x, landing.z)));
+                return path;
+            }
+            path.push(self.position);
+        }
+        path
+    }
+}
+
+pub(super) fn apply_aerodynamics(
+    // a ball held in the air is not flying
+    mut query: Query<
+        (Forces, &mut BallFlight, &CollidingEntities, &RigidBody),
+        Without<RigidBodyDisabled>,
+    >,
+    wind: Res<Wind>,
+    chunk_manager: Option<Res<ChunkManager>>,
+    time: Res<Time>,
+) {
+    for (mut forces, mut flight, colliding, rigid_body) in &mut query {
+        if *rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+
+        if colliding.is_empty() {
+            flight.in_flight = true;
+            let air_velocity = forces.linear_velocity() - wind.velocity();
+            let acceleration = aerodynamic_acceleration(air_velocity, flight.spin);
+            forces.apply_linear_acceleration(acceleration);
+            flight.spin *= 1.0 - SPIN_DECAY * time.delta_secs();
+        } else if flight.in_flight {
+            // first contact after a flight: the ground swallows some speed, backspin bites
+            // and the ball checks up
+            let position = forces.position().0;
+            let (spin_retention, landing_absorption) = match &chunk_manager {
+                Some(chunk_manager) => {
+                    let surface = surface_profile(
+                        &chunk_manager.generator.zone_type_at(position.x, position.z),
+                    );
+                    (surface.spin_retention, surface.landing_absorption)
+                }
+                None => (1.0, 0.0),
+            };
+
+            let velocity = forces.linear_velocity();
+            let horizontal = Vec3::new(velocity.x, 0.0, velocity.z);
+            let right = horizontal.normalize_or_zero().cross(Vec3::Y);
+            let backspin = flight.spin.dot(right) * spin_retention;
+            let check_up =
+                ((backspin - CHECK_UP_MIN_SPIN) / CHECK_UP_SPIN_RANGE).clamp(0.0, MAX_CHECK_UP);
+            let kept = (1.0 - check_up) * (1.0 - landing_absorption);
+            *forces.linear_velocity_mut() -= horizontal * (1.0 - kept);
+
+            flight.spin = Vec3::ZERO;
+            flight.in_flight = false;
+        }
+    }
+}
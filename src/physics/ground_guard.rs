@@ -0,0 +1,79 @@
+use avian3d::prelude::{LinearVelocity, RigidBody, RigidBodyDisabled};
+use bevy::prelude::*;
+
+use crate::chunk::chunk_manager::ChunkManager;
+use crate::objects::golfball::Golfball;
+use crate::physics::flight::{BallFlight, FlightSimulation};
+use crate::physics::wind::Wind;
+
+// seconds of flight ahead of the ball whose chunks are generated right away
+const PREDICTION_TIME: f32 = 1.0;
+const PREDICTION_STEP: f32 = 0.05;
+// seconds ahead of the ball that have to be covered by colliders for it to keep moving
+const FREEZE_LOOKAHEAD: f32 = 0.1;
+
+// The ball is held in the air until the ground in front of it has a collider.
+#[derive(Component)]
+pub(crate) struct WaitingForGround;
+
+// Forces the colliders of the chunks the ball will fly over during the next second, so they
+// are in place before it arrives, however far its own chunk loader reaches.
+pub(super) fn generate_chunks_ahead_of_ball(
+    golfball: Single<(&Transform, &LinearVelocity, &BallFlight, &RigidBody), With<Golfball>>,
+    chunk_manager: Option<ResMut<ChunkManager>>,
+    wind: Res<Wind>,
+    mut commands: Commands,
+) {
+    let Some(mut chunk_manager) = chunk_manager else {
+        return;
+    };
+    let (transform, velocity, flight, rigid_body) = golfball.into_inner();
+    if *rigid_body != RigidBody::Dynamic || velocity.0 == Vec3::ZERO {
+        return;
+    }
+
+    let mut simulation = FlightSimulation::new(
+        transform.translation,
+        velocity.0,
+        flight.spin,
+        wind.velocity(),
+    );
+    let mut position = transform.translation;
+    for _ in 0..=(PREDICTION_TIME / PREDICTION_STEP) as usize {
+        chunk_manager.force_chunk(
+            &mut commands,
+            ChunkManager::chunk_pos_at(position.x, position.z),
+        );
+        simulation.step(PREDICTION_STEP);
+        position = simulation.position;
+    }
+}
+
+// Runs before every physics step: freezes the ball where it is when the ground right ahead
+// of it has no collider yet, and lets it go on with its velocity once it has.
+pub(super) fn freeze_ball_without_ground(
+    golfball: Single<(Entity, &Transform, &LinearVelocity, Has<WaitingForGround>), With<Golfball>>,
+    chunk_manager: Option<Res<ChunkManager>>,
+    mut commands: Commands,
+) {
+    let Some(chunk_manager) = chunk_manager else {
+        return;
+    };
+    let (entity, transform, velocity, waiting) = golfball.into_inner();
+
+    let ahead = transform.translation + velocity.0 * FREEZE_LOOKAHEAD;
+    let ready = [transform.translation, ahead].iter().all(|position| {
+        chunk_manager.has_collider(ChunkManager::chunk_pos_at(position.x, position.z))
+    });
+
+    if !ready && !waiting {
+        info!("holding the ball until the ground ahead is generated");
+        commands
+            .entity(entity)
+            .insert((WaitingForGround, RigidBodyDisabled));
+    } else if ready && waiting {
+        commands
+            .entity(entity)
+            .remove::<(WaitingForGround, RigidBodyDisabled)>();
+    }
+}
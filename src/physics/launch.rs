@@ -0,0 +1,165 @@
+use avian3d::prelude::{LinearVelocity, RigidBody};
+use bevy::prelude::*;
+
+use crate::club::Club;
+use crate::physics::flight::{BallFlight, FlightSimulation};
+
+pub const GRAVITY: f32 = 9.81;
+
+// how far off the ideal line the ball starts per unit of precision offset (radians)
+const SIDE_ANGLE_PER_PRECISION: f32 = 0.1;
+// sidespin in rad/s per unit of precision offset at full power
+const SIDESPIN_PER_PRECISION: f32 = 700.0;
+// ball speed lost per unit of precision offset outside the club's grace window
+const MISHIT_SPEED_LOSS: f32 = 0.8;
+
+// Everything needed to put the ball into flight. Independent of the physics step, so a
+// shot can be reasoned about in m/s and metres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchConditions {
+    // ball speed in m/s
+    pub speed: f32,
+    // vertical launch angle in radians
+    pub launch_angle: f32,
+    // start line relative to the aim direction in radians, positive is to the right
+    pub side_angle: f32,
+    // rad/s, positive makes the ball climb
+    pub backspin: f32,
+    // rad/s, positive curves the ball to the right
+    pub sidespin: f32,
+}
+
+// Pure launch model: turns the club, the power and precision markers of the shoot
+// challenge (see `AimChallengeResource`), the loft in degrees and the lie's strike
+// multiplier into launch conditions.
+// Early hits (positive precision) are pushed and sliced, late hits are pulled and hooked.
+pub fn launch_conditions(
+    club: &Club,
+    power: f32,
+    loft: f32,
+    precision: f32,
+    lie: f32,
+) -> LaunchConditions {
+    let power = power.clamp(0.0, 1.0);
+    let mishit = (precision.abs() - club.precision_grace).max(0.0);
+    let smash = (1.0 - mishit * MISHIT_SPEED_LOSS).max(0.4);
+
+    LaunchConditions {
+        speed: club.max_ball_speed * power * lie * smash,
+        launch_angle: club.clamp_loft(loft).to_radians(),
+        side_angle: precision * SIDE_ANGLE_PER_PRECISION,
+        backspin: club.backspin * power * lie,
+        sidespin: precision * SIDESPIN_PER_PRECISION * power,
+    }
+}
+
+impl LaunchConditions {
+    fn start_line(&self, aim: Vec3) -> Vec3 {
+        Vec3::new(aim.x, 0.0, aim.z)
+            .normalize_or(Vec3::X)
+            .rotate_y(-self.side_angle)
+    }
+
+    pub fn linear_velocity(&self, aim: Vec3) -> Vec3 {
+        let forward = self.start_line(aim);
+        forward * self.speed * self.launch_angle.cos()
+            + Vec3::Y * self.speed * self.launch_angle.sin()
+    }
+
+    pub fn angular_velocity(&self, aim: Vec3) -> Vec3 {
+        let right = self.start_line(aim).cross(Vec3::Y);
+        right * self.backspin - Vec3::Y * self.sidespin
+    }
+
+    // carry on flat ground in still air, following the ball's flight model
+    pub fn carry_distance(&self) -> f32 {
+        let aim = Vec3::X;
+        let mut simulation = FlightSimulation::new(
+            Vec3::ZERO,
+            self.linear_velocity(aim),
+            self.angular_velocity(aim),
+            Vec3::ZERO,
+        );
+        while simulation.velocity.y > 0.0 || simulation.position.y > 0.0 {
+            simulation.step(1.0 / 64.0);
+        }
+        simulation.position.xz().length()
+    }
+}
+
+// Launch waiting for the ball's rigid body to become dynamic again.
+#[derive(Component)]
+pub struct PendingLaunch {
+    pub linear_velocity: Vec3,
+    pub angular_velocity: Vec3,
+}
+
+impl PendingLaunch {
+    pub fn new(conditions: &LaunchConditions, aim: Vec3) -> Self {
+        PendingLaunch {
+            linear_velocity: conditions.linear_velocity(aim),
+            angular_velocity: conditions.angular_velocity(aim),
+        }
+    }
+}
+
+pub(super) fn apply_pending_launch(
+    mut commands: Commands,
+    query: Query<(
+        Entity,
+        &PendingLaunch,
+        &RigidBody,
+        &mut LinearVelocity,
+        &mut BallFlight,
+    )>,
+) {
+    for (entity, launch, rigid_body, mut linear_velocity, mut flight) in query {
+        if *rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+        linear_velocity.0 = launch.linear_velocity;
+        flight.launch(launch.angular_velocity);
+        commands.entity(entity).remove::<PendingLaunch>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::club::CLUBS;
+
+    fn club(name: &str) -> &'static Club {
+        CLUBS.iter().find(|club| club.name == name).unwrap()
+    }
+
+    fn full_shot(club: &Club) -> LaunchConditions {
+        launch_conditions(club, 1.0, club.default_loft, 0.0, 1.0)
+    }
+
+    #[test]
+    fn driver_carries_about_245_metres() {
+        let carry = full_shot(club("Driver")).carry_distance();
+        assert!((carry - 245.0).abs() < 10.0, "driver carried {carry} m");
+    }
+
+    #[test]
+    fn longer_clubs_carry_further() {
+        let carries = ["Driver", "5 Iron", "9 Iron", "Sand Wedge"]
+            .map(|name| full_shot(club(name)).carry_distance());
+        assert!(
+            carries.windows(2).all(|pair| pair[0] > pair[1]),
+            "{carries:?}"
+        );
+    }
+
+    #[test]
+    fn mishits_and_bad_lies_lose_distance() {
+        let driver = club("Driver");
+        let centered = full_shot(driver).carry_distance();
+        let mishit = launch_conditions(driver, 1.0, driver.default_loft, 0.5, 1.0);
+        let rough = launch_conditions(driver, 1.0, driver.default_loft, 0.0, 0.8);
+        assert!(mishit.carry_distance() < centered);
+        assert!(rough.carry_distance() < centered);
+        assert!(mishit.side_angle > 0.0 && mishit.sidespin > 0.0);
+    }
+}
@@ -0,0 +1,35 @@
+pub mod flight;
+pub mod ground_guard;
+pub mod launch;
+pub mod surface;
+pub mod wind;
+
+use bevy::app::{App, FixedUpdate, Plugin, Update};
+use bevy::prelude::IntoScheduleConfigs;
+
+pub struct BallPhysicsPlugin;
+
+impl Plugin for BallPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<wind::Wind>()
+            .add_systems(
+                Update,
+                (
+                    launch::apply_pending_launch,
+                    wind::update_wind,
+                    // a chunk done in the background this frame doesn't need to be forced
+                    ground_guard::generate_chunks_ahead_of_ball
+                        .after(crate::chunk::chunk_manager::receive_generated_chunks),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    ground_guard::freeze_ball_without_ground,
+                    flight::apply_aerodynamics,
+                    surface::apply_rolling_resistance,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,129 @@
+use avian3d::prelude::{CollidingEntities, Forces, Restitution, RigidBody, RigidBodyForces};
+use bevy::prelude::*;
+
+use crate::chunk::chunk_manager::ChunkManager;
+use crate::generation::ZoneType;
+use crate::objects::golfball::Golfball;
+
+// How the ground under the ball treats it. The chunk colliders are one heightfield each, so
+// instead of per-cell materials the ball looks up the zone it touches every step.
+pub struct SurfaceProfile {
+    // deceleration in m/s² while rolling
+    pub rolling_resistance: f32,
+    pub restitution: f32,
+    // fraction of the landing backspin that still bites
+    pub spin_retention: f32,
+    // fraction of the horizontal speed swallowed on landing
+    pub landing_absorption: f32,
+}
+
+pub fn surface_profile(zone_type: &ZoneType) -> SurfaceProfile {
+    match zone_type {
+        ZoneType::DeadZone => SurfaceProfile {
+            rolling_resistance: 20.0,
+            restitution: 0.0,
+            spin_retention: 0.0,
+            landing_absorption: 1.0,
+        },
+        ZoneType::Tee | ZoneType::Fairway => SurfaceProfile {
+            rolling_resistance: 0.5,
+            restitution: 0.3,
+            spin_retention: 1.0,
+            landing_absorption: 0.0,
+        },
+        ZoneType::FirstCut => SurfaceProfile {
+            rolling_resistance: 1.0,
+            restitution: 0.25,
+            spin_retention: 0.8,
+            landing_absorption: 0.1,
+        },
+        ZoneType::Rough => SurfaceProfile {
+            rolling_resistance: 1.8,
+            restitution: 0.2,
+            spin_retention: 0.55,
+            landing_absorption: 0.2,
+        },
+        ZoneType::Green => SurfaceProfile {
+            rolling_resistance: 0.35,
+            restitution: 0.2,
+            spin_retention: 1.0,
+            landing_absorption: 0.1,
+        },
+        ZoneType::Fringe => SurfaceProfile {
+            rolling_resistance: 0.45,
+            restitution: 0.25,
+            spin_retention: 1.0,
+            landing_absorption: 0.05,
+        },
+        ZoneType::Offtrack => SurfaceProfile {
+            rolling_resistance: 2.5,
+            restitution: 0.15,
+            spin_retention: 0.4,
+            landing_absorption: 0.3,
+        },
+        ZoneType::WasteArea => SurfaceProfile {
+            rolling_resistance: 3.0,
+            restitution: 0.1,
+            spin_retention: 0.3,
+            landing_absorption: 0.6,
+        },
+        // hard, the ball skips off it
+        ZoneType::Rock => SurfaceProfile {
+            rolling_resistance: 1.2,
+            restitution: 0.5,
+            spin_retention: 0.2,
+            landing_absorption: 0.05,
+        },
+        ZoneType::Snow => SurfaceProfile {
+            rolling_resistance: 10.0,
+            restitution: 0.0,
+            spin_retention: 0.0,
+            landing_absorption: 0.95,
+        },
+        ZoneType::Bunker => SurfaceProfile {
+            rolling_resistance: 8.0,
+            restitution: 0.0,
+            spin_retention: 0.1,
+            landing_absorption: 0.9,
+        },
+    }
+}
+
+pub(super) fn apply_rolling_resistance(
+    mut query: Query<(Forces, &mut Restitution, &CollidingEntities, &RigidBody), With<Golfball>>,
+    chunk_manager: Option<Res<ChunkManager>>,
+    time: Res<Time>,
+) {
+    let Some(chunk_manager) = chunk_manager else {
+        return;
+    };
+
+    for (mut forces, mut restitution, colliding, rigid_body) in &mut query {
+        if *rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+
+        let position = forces.position().0;
+        let surface =
+            surface_profile(&chunk_manager.generator.zone_type_at(position.x, position.z));
+        if restitution.coefficient != surface.restitution {
+            restitution.coefficient = surface.restitution;
+        }
+
+        if colliding.is_empty() {
+            continue;
+        }
+
+        let velocity = forces.linear_velocity();
+        let horizontal = Vec3::new(velocity.x, 0.0, velocity.z);
+        let speed = horizontal.length();
+        if speed <= f32::EPSILON {
+            continue;
+        }
+
+        let slowdown = (surface.rolling_resistance * time.delta_secs()).min(speed);
+        *forces.linear_velocity_mut() -= horizontal / speed * slowdown;
+        // keep the rolling spin in line, otherwise friction speeds the ball back up
+        *forces.angular_velocity_mut() *= (speed - slowdown) / speed;
+    }
+}
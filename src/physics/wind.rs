@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use noise::{NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::chunk::chunk_manager::ChunkManager;
+
+const MAX_BASE_SPEED: f32 = 8.0;
+// how far (radians) the wind direction drifts away from its base during a hole
+const DIRECTION_DRIFT: f32 = 0.35;
+// seconds for the direction to drift and for a gust to build up
+const DRIFT_PERIOD: f64 = 40.0;
+const GUST_PERIOD: f64 = 4.0;
+
+// Global wind, derived from the course seed and changing over the time spent on a hole.
+#[derive(Resource, Default)]
+pub struct Wind {
+    seed: Option<u32>,
+    perlin: Perlin,
+    // heading the wind blows towards in the xz plane, radians
+    base_direction: f32,
+    // m/s
+    base_speed: f32,
+    // fraction of the base speed a gust can add or take away
+    gustiness: f32,
+    elapsed: f32,
+    current: Vec3,
+}
+
+impl Wind {
+    // `exposure` scales the wind to how sheltered the course is
+    pub fn from_seed(seed: u32, exposure: f32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed as u64 ^ 0x77_1d);
+        Wind {
+            seed: Some(seed),
+            perlin: Perlin::new(seed.wrapping_add(1)),
+            base_direction: rng.random_range(0.0..std::f32::consts::TAU),
+            base_speed: rng.random_range(0.0..MAX_BASE_SPEED) * exposure,
+            gustiness: rng.random_range(0.1..0.6),
+            elapsed: 0.0,
+            current: Vec3::ZERO,
+        }
+    }
+
+    pub fn sample(&self, elapsed: f32) -> Vec3 {
+        let t = elapsed as f64;
+        let drift = self.perlin.get([t / DRIFT_PERIOD, 0.5]) as f32 * DIRECTION_DRIFT;
+        let gust = self.perlin.get([t / GUST_PERIOD, 10.5]) as f32 * self.gustiness;
+
+        let direction = self.base_direction + drift;
+        let speed = (self.base_speed * (1.0 + gust)).max(0.0);
+        Vec3::new(direction.cos(), 0.0, direction.sin()) * speed
+    }
+
+    // m/s in world space
+    pub fn velocity(&self) -> Vec3 {
+        self.current
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.current.length()
+    }
+}
+
+pub(super) fn update_wind(
+    mut wind: ResMut<Wind>,
+    chunk_manager: Option<Res<ChunkManager>>,
+    time: Res<Time>,
+) {
+    let Some(chunk_manager) = chunk_manager else {
+        return;
+    };
+
+    let seed = chunk_manager.generator.seed();
+    if wind.seed != Some(seed) {
+        *wind = Wind::from_seed(seed, chunk_manager.generator.wind_exposure());
+    }
+
+    wind.elapsed += time.delta_secs();
+    wind.current = wind.sample(wind.elapsed);
+}
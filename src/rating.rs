@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+
+use crate::chunk::HazardKind;
+use crate::generation::{TerrainGenerator, ZoneType};
+
+// every crossing of water, sand or rock on the way to the hole plays like this many extra metres
+const HAZARD_PENALTY_METERS: f32 = 40.0;
+// every metre the green lies above the tee plays like this many extra metres
+const ELEVATION_FACTOR: f32 = 1.0;
+const PAR_3_MAX_LENGTH: f32 = 230.0;
+const PAR_4_MAX_LENGTH: f32 = 430.0;
+// metres a scratch and a bogey golfer cover with one full shot
+const SCRATCH_SHOT: f32 = 230.0;
+const BOGEY_SHOT: f32 = 170.0;
+// strokes around and on the green, however long the hole is
+const SCRATCH_SHORT_GAME: f32 = 2.3;
+const BOGEY_SHORT_GAME: f32 = 3.0;
+// hazards this close to a landing spot or the hole are in play
+const LANDING_RADIUS: f32 = 25.0;
+// directions the ground around a landing spot is looked at in, on two rings
+const LANDING_SAMPLES: usize = 12;
+// fairways narrower than this cost strokes at every landing spot
+const COMFORTABLE_WIDTH: f32 = 50.0;
+// strokes per obstacle for a scratch and a bogey golfer
+const SCRATCH_OBSTACLE: f32 = 0.08;
+const BOGEY_OBSTACLE: f32 = 0.2;
+// turns the gap between bogey and scratch rating of one hole into the usual slope scale of an
+// eighteen hole round, where 113 is average
+const SLOPE_FACTOR: f32 = 5.381 * 18.0;
+
+// How hard a hole is, rated like a real course from its layout.
+#[derive(Clone, Debug)]
+pub struct CourseRating {
+    pub par: u32,
+    pub length: f32,
+    // height of the hole above the tee
+    pub elevation_change: f32,
+    // strokes a scratch golfer is expected to take
+    pub rating: f32,
+    pub slope: u32,
+}
+
+pub fn rate_course(generator: &dyn TerrainGenerator) -> CourseRating {
+    let course = generator.course_layout();
+    let length = course.length();
+    let start = course.start();
+    let hole = course.end();
+    let elevation_change =
+        generator.height_at(hole.x, hole.y) - generator.height_at(start.x, start.y);
+
+    // the length plus the extra metres for climbing and crossing hazards
+    let playing_length = length
+        + elevation_change * ELEVATION_FACTOR
+        + hazard_crossings(generator) as f32 * HAZARD_PENALTY_METERS;
+    let rating = SCRATCH_SHORT_GAME
+        + playing_length / SCRATCH_SHOT
+        + obstacles(generator, SCRATCH_SHOT) * SCRATCH_OBSTACLE;
+    // strokes a bogey golfer is expected to take
+    let bogey_rating = BOGEY_SHORT_GAME
+        + playing_length / BOGEY_SHOT
+        + obstacles(generator, BOGEY_SHOT) * BOGEY_OBSTACLE;
+
+    CourseRating {
+        par: par(playing_length),
+        length,
+        elevation_change,
+        rating,
+        slope: slope(rating, bogey_rating),
+    }
+}
+
+fn par(playing_length: f32) -> u32 {
+    match playing_length {
+        ..PAR_3_MAX_LENGTH => 3,
+        ..PAR_4_MAX_LENGTH => 4,
+        _ => 5,
+    }
+}
+
+// how much harder the hole is for a bogey golfer than for a scratch golfer
+fn slope(rating: f32, bogey_rating: f32) -> u32 {
+    ((bogey_rating - rating) * SLOPE_FACTOR)
+        .round()
+        .clamp(55.0, 155.0) as u32
+}
+
+// how often the course line enters ground that costs a stroke or a penalty
+fn hazard_crossings(generator: &dyn TerrainGenerator) -> usize {
+    let course = generator.course_layout();
+    let mut crossings = 0;
+    let mut in_hazard = false;
+    for step in 0..=course.length() as usize {
+        let p = course.point_at(step as f32);
+        let zone = generator.zone_type_at(p.x, p.y);
+        let hazard = zone.is_unplayable() || zone == ZoneType::Bunker;
+        if hazard && !in_hazard {
+            crossings += 1;
+        }
+        in_hazard = hazard;
+    }
+    crossings
+}
+
+// Hazards near the spots where shots of `shot_length` land, and how narrow the fairway is
+// there. The green always counts as a landing spot. Water, rock and snow that are not part of
+// the hazards, like the lakes of island courses, count as one more obstacle.
+fn obstacles(generator: &dyn TerrainGenerator, shot_length: f32) -> f32 {
+    let course = generator.course_layout();
+    let length = course.length();
+
+    let mut landings: Vec<f32> = (1..)
+        .map(|shot| shot as f32 * shot_length)
+        .take_while(|arc_length| *arc_length < length - LANDING_RADIUS)
+        .collect();
+    landings.push(length);
+
+    landings
+        .into_iter()
+        .map(|arc_length| {
+            let landing = course.point_at(arc_length);
+            let hazards = generator
+                .hazards()
+                .iter()
+                .filter(|hazard| {
+                    Vec2::new(hazard.x, hazard.y).distance(landing) - hazard.size < LANDING_RADIUS
+                })
+                .collect::<Vec<_>>();
+            // a pond in the list is already counted
+            let unlisted_unplayable = !hazards.iter().any(|hazard| hazard.kind == HazardKind::Pond)
+                && unplayable_near(generator, landing);
+            let width = course.half_width_at(arc_length) * 2.0;
+            hazards.len() as f32
+                + if unlisted_unplayable { 1.0 } else { 0.0 }
+                + ((COMFORTABLE_WIDTH - width) / COMFORTABLE_WIDTH).max(0.0)
+        })
+        .sum()
+}
+
+// whether the ball would have to be dropped somewhere within `LANDING_RADIUS` of `landing`
+fn unplayable_near(generator: &dyn TerrainGenerator, landing: Vec2) -> bool {
+    [LANDING_RADIUS * 0.5, LANDING_RADIUS]
+        .into_iter()
+        .any(|radius| {
+            (0..LANDING_SAMPLES).any(|i| {
+                let angle = i as f32 / LANDING_SAMPLES as f32 * std::f32::consts::TAU;
+                let p = landing + Vec2::from_angle(angle) * radius;
+                generator.zone_type_at(p.x, p.y).is_unplayable()
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::grasslands::GrasslandsGenerator;
+    use crate::generation::links::LinksGenerator;
+
+    #[test]
+    fn par_changes_at_the_maximum_lengths() {
+        let pars = [0.0, 229.9, 230.0, 429.9, 430.0, 700.0].map(par);
+        assert_eq!(pars, [3, 3, 4, 4, 5, 5]);
+    }
+
+    #[test]
+    fn an_average_gap_between_bogey_and_scratch_has_slope_113() {
+        let gap = 113.0 / SLOPE_FACTOR;
+        assert_eq!(slope(4.0, 4.0 + gap), 113);
+    }
+
+    #[test]
+    fn slope_stays_on_the_usual_scale() {
+        assert_eq!(slope(4.0, 4.0), 55);
+        assert_eq!(slope(4.0, 10.0), 155);
+    }
+
+    #[test]
+    fn generated_holes_are_rated_close_to_their_par() {
+        for seed in 0..8 {
+            let generators: [Box<dyn TerrainGenerator>; 2] = [
+                Box::new(GrasslandsGenerator::new(seed)),
+                Box::new(LinksGenerator::new(seed)),
+            ];
+            for generator in generators {
+                let rating = rate_course(generator.as_ref());
+                assert!(
+                    (rating.rating - rating.par as f32).abs() < 1.5,
+                    "seed {seed}: {rating:?}"
+                );
+                assert!(
+                    (55..=155).contains(&rating.slope),
+                    "seed {seed}: {rating:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use bevy::prelude::*;
+
+use crate::{
+    generation::{TerrainGenerator, ZoneType},
+    objects::golfball::{Golfball, PrevPosition},
+    physics::flight::BALL_RADIUS,
+    state::state::AppState,
+    ui::shoot_challenge::AimChallengeState,
+};
+
+// how far the drop is placed outside the hazard edge, roughly one club length
+const DROP_DISTANCE: f32 = 1.0;
+const EDGE_BISECTION_STEPS: usize = 12;
+
+pub struct RulesPlugin;
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OutOfBounds>()
+            .init_resource::<BallTrack>()
+            .add_systems(OnEnter(AppState::InShot), clear_ball_track)
+            .add_systems(Update, record_ball_track.run_if(in_state(AppState::InShot)))
+            .add_systems(
+                Update,
+                cycle_drop_input_handler
+                    .run_if(resource_exists::<PenaltyDrop>)
+                    .run_if(in_state(AppState::Aim))
+                    .run_if(in_state(AimChallengeState::Idle)),
+            )
+            .add_systems(OnExit(AppState::Aim), remove_penalty_drop);
+    }
+}
+
+// Everything further than `distance` metres from the course line is out of bounds.
+#[derive(Resource)]
+pub struct OutOfBounds {
+    pub distance: f32,
+}
+
+impl Default for OutOfBounds {
+    fn default() -> Self {
+        OutOfBounds { distance: 80.0 }
+    }
+}
+
+// Positions of the ball during the current shot, used to find where it crossed into a hazard.
+#[derive(Resource, Default)]
+pub struct BallTrack(Vec<Vec3>);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PenaltyReason {
+    Water,
+    OutOfBounds,
+    Unplayable,
+}
+
+impl PenaltyReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PenaltyReason::Water => "WATER HAZARD",
+            PenaltyReason::OutOfBounds => "OUT OF BOUNDS",
+            PenaltyReason::Unplayable => "UNPLAYABLE LIE",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DropKind {
+    PreviousSpot,
+    HazardEdge,
+}
+
+impl DropKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DropKind::PreviousSpot => "PREVIOUS SPOT",
+            DropKind::HazardEdge => "HAZARD EDGE",
+        }
+    }
+}
+
+pub struct DropOption {
+    pub kind: DropKind,
+    pub position: Vec3,
+}
+
+// A penalty that was just given, the player picks one of the drop options while aiming.
+#[derive(Resource)]
+pub struct PenaltyDrop {
+    pub reason: PenaltyReason,
+    pub options: Vec<DropOption>,
+    pub selected: usize,
+}
+
+impl PenaltyDrop {
+    pub fn selected(&self) -> &DropOption {
+        &self.options[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.options.len();
+    }
+
+    // every penalty costs one stroke, stroke and distance as well as a drop
+    pub fn strokes(&self) -> u32 {
+        1
+    }
+}
+
+// Decides what happens to a ball that came to rest at `position`, `None` if it is in play.
+pub fn rule_ball_at_rest(
+    generator: &dyn TerrainGenerator,
+    out_of_bounds: &OutOfBounds,
+    track: &BallTrack,
+    position: Vec3,
+    previous: Vec3,
+) -> Option<PenaltyDrop> {
+    let previous_spot = DropOption {
+        kind: DropKind::PreviousSpot,
+        position: previous,
+    };
+
+    // stroke and distance
+    if generator.course_layout().distance_to_path(position.xz()) > out_of_bounds.distance {
+        return Some(PenaltyDrop {
+            reason: PenaltyReason::OutOfBounds,
+            options: vec![previous_spot],
+            selected: 0,
+        });
+    }
+
+    let zone = generator.zone_type_at(position.x, position.z);
+    if zone.is_unplayable() {
+        let mut options = Vec::new();
+        if let Some(position) = hazard_edge_drop(generator, &track.0) {
+            options.push(DropOption {
+                kind: DropKind::HazardEdge,
+                position,
+            });
+        }
+        options.push(previous_spot);
+        return Some(PenaltyDrop {
+            reason: if zone == ZoneType::DeadZone {
+                PenaltyReason::Water
+            } else {
+                PenaltyReason::Unplayable
+            },
+            options,
+            selected: 0,
+        });
+    }
+
+    None
+}
+
+// Finds where the recorded track last crossed into water, rock or snow and drops the ball just
+// outside.
+fn hazard_edge_drop(generator: &dyn TerrainGenerator, track: &[Vec3]) -> Option<Vec3> {
+    let in_hazard = |p: Vec2| generator.zone_type_at(p.x, p.y).is_unplayable();
+
+    let (mut outside, mut inside) = track
+        .windows(2)
+        .rev()
+        .map(|pair| (pair[0].xz(), pair[1].xz()))
+        .find(|(from, to)| !in_hazard(*from) && in_hazard(*to))?;
+    let back = (outside - inside).normalize_or_zero();
+
+    for _ in 0..EDGE_BISECTION_STEPS {
+        let middle = (outside + inside) / 2.0;
+        if in_hazard(middle) {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+
+    let drop = outside + back * DROP_DISTANCE;
+    if in_hazard(drop) {
+        return None;
+    }
+    Some(Vec3::new(
+        drop.x,
+        generator.height_at(drop.x, drop.y) + BALL_RADIUS,
+        drop.y,
+    ))
+}
+
+fn clear_ball_track(mut track: ResMut<BallTrack>) {
+    track.0.clear();
+}
+
+fn record_ball_track(mut track: ResMut<BallTrack>, golfball: Single<&Transform, With<Golfball>>) {
+    track.0.push(golfball.translation);
+}
+
+fn cycle_drop_input_handler(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut penalty_drop: ResMut<PenaltyDrop>,
+    golfball: Single<(&mut Transform, &mut PrevPosition), With<Golfball>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    penalty_drop.next();
+    let (mut transform, mut prev_position) = golfball.into_inner();
+    transform.translation = penalty_drop.selected().position;
+    prev_position.xyz = transform.translation;
+}
+
+fn remove_penalty_drop(mut commands: Commands) {
+    commands.remove_resource::<PenaltyDrop>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Hazard;
+    use crate::generation::Prop;
+    use crate::generation::course_layout::CourseLayout;
+    use crate::generation::grasslands::GrasslandsGenerator;
+
+    // metres from the tee along the start of the course where the hazard begins
+    const HAZARD_START: f32 = 20.0;
+
+    // The course of `GrasslandsGenerator` on flat ground, which turns into `hazard` from
+    // `HAZARD_START` metres in front of the tee on.
+    struct FlatCourse {
+        course: GrasslandsGenerator,
+        hazard: ZoneType,
+    }
+
+    impl FlatCourse {
+        fn new(hazard: ZoneType) -> Self {
+            FlatCourse {
+                course: GrasslandsGenerator::new(7),
+                hazard,
+            }
+        }
+
+        // `distance` metres in front of the tee, along the start of the course
+        fn ahead(&self, distance: f32) -> Vec3 {
+            let layout = self.course.course_layout();
+            let p = layout.start() + layout.tangent_at(0.0) * distance;
+            Vec3::new(p.x, BALL_RADIUS, p.y)
+        }
+    }
+
+    impl TerrainGenerator for FlatCourse {
+        fn seed(&self) -> u32 {
+            self.course.seed()
+        }
+        fn height_at(&self, _x: f32, _y: f32) -> f32 {
+            0.0
+        }
+        fn props_in_chunk(&self, _offset: (i32, i32)) -> Vec<Prop> {
+            Vec::new()
+        }
+        fn course_layout(&self) -> &CourseLayout {
+            self.course.course_layout()
+        }
+        fn start(&self) -> [f32; 2] {
+            self.course.start()
+        }
+        fn hole(&self) -> [f32; 2] {
+            self.course.hole()
+        }
+        fn zone_type_at(&self, x: f32, y: f32) -> ZoneType {
+            let layout = self.course.course_layout();
+            let along = (Vec2::new(x, y) - layout.start()).dot(layout.tangent_at(0.0));
+            if along < HAZARD_START {
+                ZoneType::Fairway
+            } else {
+                // `ZoneType` can't be cloned
+                match self.hazard {
+                    ZoneType::DeadZone => ZoneType::DeadZone,
+                    ZoneType::Rock => ZoneType::Rock,
+                    _ => ZoneType::Snow,
+                }
+            }
+        }
+        fn hazards_in_chunk(&self, _world_offset: [i32; 2]) -> Vec<Hazard> {
+            Vec::new()
+        }
+        fn hazards(&self) -> &[Hazard] {
+            &[]
+        }
+    }
+
+    // a ball rolled straight from the tee into the hazard, at rest `distance` metres in front
+    fn roll_in(generator: &FlatCourse, distance: f32) -> Option<PenaltyDrop> {
+        let track = BallTrack(
+            (0..=distance as usize)
+                .map(|i| generator.ahead(i as f32))
+                .collect(),
+        );
+        rule_ball_at_rest(
+            generator,
+            &OutOfBounds::default(),
+            &track,
+            generator.ahead(distance),
+            generator.ahead(0.0),
+        )
+    }
+
+    #[test]
+    fn a_ball_in_play_is_not_ruled() {
+        let generator = FlatCourse::new(ZoneType::DeadZone);
+        assert!(roll_in(&generator, 10.0).is_none());
+    }
+
+    #[test]
+    fn out_of_bounds_is_only_played_again_from_the_previous_spot() {
+        let generator = FlatCourse::new(ZoneType::DeadZone);
+        let layout = generator.course_layout();
+        let beyond = layout.start() - layout.tangent_at(0.0) * 90.0;
+        let position = Vec3::new(beyond.x, BALL_RADIUS, beyond.y);
+        assert!(layout.distance_to_path(beyond) > OutOfBounds::default().distance);
+
+        let previous = generator.ahead(5.0);
+        let drop = rule_ball_at_rest(
+            &generator,
+            &OutOfBounds::default(),
+            &BallTrack::default(),
+            position,
+            previous,
+        )
+        .unwrap();
+        assert!(drop.reason == PenaltyReason::OutOfBounds);
+        assert_eq!(drop.strokes(), 1);
+        assert_eq!(drop.options.len(), 1);
+        assert!(drop.selected().kind == DropKind::PreviousSpot);
+        assert_eq!(drop.selected().position, previous);
+    }
+
+    #[test]
+    fn drops_outside_the_edge_where_the_ball_crossed_into_the_hazard() {
+        for (hazard, reason) in [
+            (ZoneType::DeadZone, PenaltyReason::Water),
+            (ZoneType::Rock, PenaltyReason::Unplayable),
+            (ZoneType::Snow, PenaltyReason::Unplayable),
+        ] {
+            let generator = FlatCourse::new(hazard);
+            let drop = roll_in(&generator, 30.0).unwrap();
+            assert!(drop.reason == reason);
+            assert_eq!(drop.strokes(), 1);
+            assert_eq!(drop.options.len(), 2);
+            // the edge is offered first, the previous spot is still there
+            assert!(drop.selected().kind == DropKind::HazardEdge);
+            assert!(drop.options[1].kind == DropKind::PreviousSpot);
+            let expected = generator.ahead(HAZARD_START - DROP_DISTANCE);
+            assert!(
+                drop.selected().position.distance(expected) < 0.01,
+                "{} instead of {expected}",
+                drop.selected().position
+            );
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
+
+use crate::{
+    chunk::chunk_manager::ChunkManager,
+    rating::{CourseRating, rate_course},
+    state::state::AppState,
+};
+
+const AVERAGE_SLOPE: f32 = 113.0;
+
+pub struct ScorecardPlugin;
+impl Plugin for ScorecardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scorecard>()
+            .add_systems(Update, start_hole_on_new_course)
+            .add_systems(OnEnter(AppState::PostScore), finish_hole);
+    }
+}
+
+pub struct HoleScore {
+    // `ChunkManager::course_id` of the hole
+    pub course_id: u32,
+    pub par: u32,
+    pub rating: CourseRating,
+    pub strokes: u32,
+    pub penalties: u32,
+    pub holed: bool,
+}
+
+impl HoleScore {
+    pub fn total(&self) -> u32 {
+        self.strokes + self.penalties
+    }
+
+    pub fn to_par(&self) -> i32 {
+        self.total() as i32 - self.par as i32
+    }
+
+    // score against the rating of the hole, scaled to a hole of average slope
+    pub fn differential(&self) -> f32 {
+        (self.total() as f32 - self.rating.rating) * AVERAGE_SLOPE / self.rating.slope as f32
+    }
+
+    pub fn term(&self) -> String {
+        if self.total() == 1 {
+            return String::from("Hole in One");
+        }
+        match self.to_par() {
+            ..=-3 => String::from("Albatross"),
+            -2 => String::from("Eagle"),
+            -1 => String::from("Birdie"),
+            0 => String::from("Par"),
+            1 => String::from("Bogey"),
+            2 => String::from("Double Bogey"),
+            3 => String::from("Triple Bogey"),
+            over => format!("+{over}"),
+        }
+    }
+}
+
+// Scores of all holes played this session. Lives outside the course, so it survives
+// every `Regenerate` cycle.
+#[derive(Resource, Default)]
+pub struct Scorecard {
+    holes: Vec<HoleScore>,
+}
+
+impl Scorecard {
+    pub fn current(&self) -> Option<&HoleScore> {
+        self.holes.last()
+    }
+
+    pub fn hole_number(&self) -> usize {
+        self.holes.len()
+    }
+
+    pub fn add_stroke(&mut self) {
+        if let Some(hole) = self.holes.last_mut() {
+            hole.strokes += 1;
+        }
+    }
+
+    pub fn add_penalty(&mut self, strokes: u32) {
+        if let Some(hole) = self.holes.last_mut() {
+            hole.penalties += strokes;
+        }
+    }
+
+    pub fn finish_hole(&mut self) {
+        if let Some(hole) = self.holes.last_mut() {
+            hole.holed = true;
+        }
+    }
+
+    fn start_hole(&mut self, course_id: u32, rating: CourseRating) {
+        self.holes.push(HoleScore {
+            course_id,
+            par: rating.par,
+            rating,
+            strokes: 0,
+            penalties: 0,
+            holed: false,
+        });
+    }
+
+    fn completed(&self) -> impl Iterator<Item = &HoleScore> {
+        self.holes.iter().filter(|hole| hole.holed)
+    }
+
+    pub fn total_strokes(&self) -> u32 {
+        self.completed().map(HoleScore::total).sum()
+    }
+
+    pub fn total_to_par(&self) -> i32 {
+        self.completed().map(HoleScore::to_par).sum()
+    }
+}
+
+pub fn format_to_par(to_par: i32) -> String {
+    match to_par {
+        0 => String::from("E"),
+        1.. => format!("+{to_par}"),
+        _ => format!("{to_par}"),
+    }
+}
+
+// The rating of a new course, computed on the `AsyncComputeTaskPool`. The hole starts once it
+// is done.
+struct PendingRating(u32, Task<CourseRating>);
+
+fn start_hole_on_new_course(
+    mut scorecard: ResMut<Scorecard>,
+    chunk_manager: Option<Res<ChunkManager>>,
+    mut pending: Local<Option<PendingRating>>,
+) {
+    let Some(chunk_manager) = chunk_manager else {
+        return;
+    };
+
+    let course_id = chunk_manager.course_id();
+    let started = scorecard.current().map(|hole| hole.course_id) == Some(course_id);
+    // a rating of an earlier course still running is dropped, which cancels it
+    if !started && pending.as_ref().map(|rating| rating.0) != Some(course_id) {
+        let generator = chunk_manager.generator.clone();
+        let task =
+            AsyncComputeTaskPool::get().spawn(async move { rate_course(generator.as_ref()) });
+        *pending = Some(PendingRating(course_id, task));
+    }
+
+    if let Some(PendingRating(course_id, task)) = pending.as_mut()
+        && let Some(rating) = block_on(poll_once(task))
+    {
+        scorecard.start_hole(*course_id, rating);
+        *pending = None;
+    }
+}
+
+pub(crate) fn finish_hole(mut scorecard: ResMut<Scorecard>) {
+    scorecard.finish_hole();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(par: u32, rating: f32, slope: u32) -> CourseRating {
+        CourseRating {
+            par,
+            length: 0.0,
+            elevation_change: 0.0,
+            rating,
+            slope,
+        }
+    }
+
+    // a hole of `par` finished in `strokes` plus `penalties`
+    fn holed(par: u32, strokes: u32, penalties: u32) -> HoleScore {
+        HoleScore {
+            course_id: 0,
+            par,
+            rating: rating(par, par as f32, AVERAGE_SLOPE as u32),
+            strokes,
+            penalties,
+            holed: true,
+        }
+    }
+
+    #[test]
+    fn names_the_score_against_par() {
+        let terms = [
+            (5, 2),
+            (5, 3),
+            (4, 3),
+            (4, 4),
+            (3, 4),
+            (3, 5),
+            (3, 6),
+            (3, 7),
+        ]
+        .map(|(par, strokes)| holed(par, strokes, 0).term());
+        assert_eq!(
+            terms,
+            [
+                "Albatross",
+                "Eagle",
+                "Birdie",
+                "Par",
+                "Bogey",
+                "Double Bogey",
+                "Triple Bogey",
+                "+4"
+            ]
+        );
+    }
+
+    #[test]
+    fn a_hole_in_one_is_named_before_par() {
+        assert_eq!(holed(3, 1, 0).term(), "Hole in One");
+        assert_eq!(holed(5, 1, 0).term(), "Hole in One");
+    }
+
+    #[test]
+    fn penalties_count_against_par() {
+        let hole = holed(4, 4, 1);
+        assert_eq!(hole.total(), 5);
+        assert_eq!(hole.to_par(), 1);
+        assert_eq!(hole.term(), "Bogey");
+    }
+
+    #[test]
+    fn differential_is_scaled_to_the_average_slope() {
+        let mut hole = holed(4, 5, 0);
+        hole.rating = rating(4, 4.0, 113);
+        assert!((hole.differential() - 1.0).abs() < 1e-5);
+        hole.rating = rating(4, 4.0, 226);
+        assert!((hole.differential() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn only_holed_holes_are_totalled() {
+        let mut scorecard = Scorecard::default();
+        scorecard.start_hole(0, rating(4, 4.0, 113));
+        scorecard.add_stroke();
+        scorecard.add_stroke();
+        scorecard.add_penalty(1);
+        scorecard.finish_hole();
+        scorecard.start_hole(1, rating(3, 3.0, 113));
+        scorecard.add_stroke();
+        assert_eq!(scorecard.hole_number(), 2);
+        assert_eq!(scorecard.total_strokes(), 3);
+        assert_eq!(scorecard.total_to_par(), -1);
+    }
+
+    #[test]
+    fn formats_even_par_as_e() {
+        assert_eq!(format_to_par(0), "E");
+        assert_eq!(format_to_par(2), "+2");
+        assert_eq!(format_to_par(-1), "-1");
+    }
+}
@@ -1,19 +1,22 @@
-use avian3d::prelude::{Forces, RigidBodyForces};
+use avian3d::prelude::ColliderDisabled;
 use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
 use bevy::{
     app::{App, Update},
     state::state::OnExit,
 };
-use std::f32::consts::PI;
 
 use crate::camera::ActiveCamera;
 use crate::chunk::chunk_manager::ChunkManager;
-use crate::generation::ZoneType;
+use crate::club::ClubBag;
 use crate::objects::aim_tiksu::AimTiksuPlugin;
-use crate::objects::flag_pole::FlagPole;
+use crate::objects::flag_pole::{FlagPole, Pin};
 use crate::objects::golfball::Golfball;
+use crate::physics::launch::{PendingLaunch, launch_conditions};
+use crate::scorecard::Scorecard;
+use crate::state::putting::Putting;
 use crate::state::state::AppState;
+use crate::ui::green_reading::GreenReadingPlugin;
 use crate::ui::shoot_challenge::{AimChallengeResource, AimChallengeState};
 use crate::ui::trajectory::TrajectoryPlugin;
 
@@ -37,7 +40,7 @@ impl Plugin for AimStatePlugin {
                 Update,
                 wait_for_golfball_punch_delay.run_if(in_state(AimChallengeState::Finalized)),
             )
-            .add_plugins((AimTiksuPlugin, TrajectoryPlugin));
+            .add_plugins((AimTiksuPlugin, TrajectoryPlugin, GreenReadingPlugin));
     }
 }
 
@@ -50,13 +53,40 @@ pub struct AimState {
 #[derive(Component)]
 pub struct AimCamera;
 
-fn set_aim_state(mut commands: Commands, camera: Single<Entity, With<ActiveCamera>>) {
+fn set_aim_state(
+    mut commands: Commands,
+    camera: Single<Entity, With<ActiveCamera>>,
+    golfball: Single<&Transform, With<Golfball>>,
+    chunk_manager: Res<ChunkManager>,
+    mut club_bag: ResMut<ClubBag>,
+    pins: Query<Entity, With<Pin>>,
+) {
     // update camera bundle
     commands.entity(camera.entity()).insert(AimCamera);
 
+    // on the green, the putter rolls the ball flat
+    let putting = Putting::at(chunk_manager.generator.as_ref(), golfball.translation);
+    let height = if putting.is_some() {
+        club_bag.select_putter();
+        0.0
+    } else {
+        club_bag.selected().default_loft
+    };
+    // the flagstick is taken out for putts, so it can't keep the ball out of the cup
+    for pin in pins {
+        if putting.is_some() {
+            commands.entity(pin).insert(ColliderDisabled);
+        } else {
+            commands.entity(pin).remove::<ColliderDisabled>();
+        }
+    }
+    if let Some(putting) = putting {
+        commands.insert_resource(putting);
+    }
+
     // set up state resource
     commands.insert_resource(AimState {
-        height: 45.0,
+        height,
         rotation: 0.0,
     });
 }
@@ -97,29 +127,33 @@ fn unset_aim_state(mut commands: Commands, camera: Single<Entity, With<AimCamera
 
     // remove aim state resource
     commands.remove_resource::<AimState>();
+    commands.remove_resource::<Putting>();
 }
 
 fn input_handler(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut aim_state: If<ResMut<AimState>>,
+    club_bag: Res<ClubBag>,
+    putting: Option<Res<Putting>>,
     time: Res<Time>,
 ) {
     let multiplier = 0.25;
+    let club = club_bag.selected();
     if keyboard_input.pressed(KeyCode::ArrowLeft) {
         aim_state.rotation += multiplier * time.delta_secs();
     }
     if keyboard_input.pressed(KeyCode::ArrowRight) {
         aim_state.rotation -= multiplier * time.delta_secs();
     }
+    // putts are always launched flat
+    if putting.is_some() {
+        return;
+    }
     if keyboard_input.pressed(KeyCode::ArrowUp) {
-        if aim_state.height < 50.0 {
-            aim_state.height += 1.0;
-        }
+        aim_state.height = club.clamp_loft(aim_state.height + 1.0);
     }
     if keyboard_input.pressed(KeyCode::ArrowDown) {
-        if aim_state.height > 0.0 {
-            aim_state.height -= 1.0;
-        }
+        aim_state.height = club.clamp_loft(aim_state.height - 1.0);
     }
 }
 
@@ -156,55 +190,42 @@ fn execute_golfball_punch(
     transform: Single<&Transform, With<AimCamera>>,
     mut next_aim_challenge_state: ResMut<NextState<AimChallengeState>>,
     mut next_app_state: ResMut<NextState<AppState>>,
-    mut golfball: Single<(Forces, &Transform), With<Golfball>>,
+    golfball: Single<(Entity, &Transform), With<Golfball>>,
     chunk_manager: Res<ChunkManager>,
+    club_bag: Res<ClubBag>,
+    putting: Option<Res<Putting>>,
+    mut scorecard: ResMut<Scorecard>,
+    mut commands: Commands,
 ) {
-    let mut missed = false;
-    let power = aim_challenge_resource.power_marker.unwrap_or_default(); // 0 none ; 1 max
-    let mut direction = **transform;
-    direction.rotate_local_x(aim_state.height / 180.0 * PI);
-    let mut inaccuracies = 0.0;
-    let mut deviation = 0.0;
-
-    if let Some(precision) = aim_challenge_resource.precision_marker {
-        if precision != 0.0 {
-            let inaccuracy_yaw = rand::random_range(
-                precision.abs() * -std::f32::consts::FRAC_PI_2 * 0.2
-                    ..precision.abs() * std::f32::consts::FRAC_PI_2 * 0.2,
-            );
-            inaccuracies = inaccuracy_yaw;
-            // 0 +- 0.1 is precise
-            if precision.abs() > 0.1 {
-                missed = true;
-            }
-        }
+    let club = club_bag.selected();
+    let mut power = aim_challenge_resource.power_marker.unwrap_or_default(); // 0 none ; 1 max
+    if let Some(putting) = putting {
+        power = putting.scale_power(power, club);
     }
-
-    if missed {
-        let random_yaw =
-            rand::random_range(-std::f32::consts::FRAC_PI_2..std::f32::consts::FRAC_PI_2);
-        deviation = random_yaw;
-    }
-
-    let combined_rotation = deviation + inaccuracies;
-    direction.rotate_local_y(combined_rotation);
-    let final_direction = direction.forward().as_vec3();
+    let precision = aim_challenge_resource.precision_marker.unwrap_or_default();
 
     let zone_type = chunk_manager
         .generator
         .zone_type_at(golfball.1.translation.x, golfball.1.translation.z);
-    let power_ground_multiplier = match zone_type {
-        ZoneType::DeadZone => 0.0,
-        ZoneType::Clean => rand::random_range(0.98..1.0),
-        ZoneType::Offtrack => rand::random_range(0.6..0.8),
-        ZoneType::Bunker => rand::random_range(0.2..0.4),
-    };
+    let lie = club.penalties.multiplier(&zone_type);
+
+    let launch = launch_conditions(club, power, aim_state.height, precision, lie);
+    info!(
+        "launching with {:.1}m/s at {:.1}°, expecting {:.0}m carry",
+        launch.speed,
+        launch.launch_angle.to_degrees(),
+        launch.carry_distance()
+    );
 
-    let force_vector = final_direction * Vec3::splat(power * power_ground_multiplier * 10.0);
+    // missing the precision window shows up as sidespin, hooking or slicing the ball
+    let aim = transform.forward().as_vec3();
 
     // wait for tiksu
 
-    golfball.0.apply_force(force_vector);
+    commands
+        .entity(golfball.0)
+        .insert(PendingLaunch::new(&launch, aim));
+    scorecard.add_stroke();
     next_app_state.set(AppState::InShot);
     next_aim_challenge_state.set(AimChallengeState::Idle);
 }
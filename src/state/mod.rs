@@ -2,5 +2,6 @@ pub mod aim;
 pub mod inshot;
 pub mod postscore;
 pub mod presentcourse;
+pub mod putting;
 pub mod regenerate;
 pub mod state;
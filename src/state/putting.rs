@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::{
+    club::Club,
+    generation::{TerrainGenerator, ZoneType},
+    physics::surface::surface_profile,
+};
+
+// how far past the hole a full power putt rolls, relative to the distance to the hole
+const FULL_POWER_DISTANCE_FACTOR: f32 = 1.5;
+const MIN_FULL_POWER_DISTANCE: f32 = 3.0;
+
+// Present while aiming from the green: the putter is locked in, the ball is launched flat and
+// the power bar is scaled to the length of the putt.
+#[derive(Resource)]
+pub struct Putting {
+    // metres a full power putt rolls out on a flat green
+    pub full_power_distance: f32,
+}
+
+impl Putting {
+    pub fn at(generator: &dyn TerrainGenerator, position: Vec3) -> Option<Self> {
+        if generator.zone_type_at(position.x, position.z) != ZoneType::Green {
+            return None;
+        }
+
+        let [hole_x, hole_z] = generator.hole();
+        let distance = position.xz().distance(Vec2::new(hole_x, hole_z));
+        Some(Putting {
+            full_power_distance: (distance * FULL_POWER_DISTANCE_FACTOR)
+                .max(MIN_FULL_POWER_DISTANCE),
+        })
+    }
+
+    // power for the launch model, a full bar reaches `full_power_distance`
+    pub fn scale_power(&self, power: f32, putter: &Club) -> f32 {
+        let rolling_resistance = surface_profile(&ZoneType::Green).rolling_resistance;
+        let full_power_speed = (2.0 * rolling_resistance * self.full_power_distance).sqrt();
+        power * (full_power_speed / putter.max_ball_speed).min(1.0)
+    }
+}
@@ -3,7 +3,8 @@ use bevy::{ecs::system::SystemId, prelude::*};
 use crate::{
     camera::ActiveCamera,
     chunk::chunk_manager::ChunkManager,
-    generation::grasslands::GrasslandsGenerator,
+    generation::registry::GeneratorRegistry,
+    generation::validation::{CourseThresholds, PlayableCourse},
     objects::{flag_pole::FlagPole, golfball::Golfball},
     state::state::AppState,
 };
@@ -17,7 +18,11 @@ impl Plugin for RegenPlugin {
         )
         .add_systems(
             Update,
-            wait_for_delayed_systems.run_if(in_state(AppState::Regenerate)),
+            (
+                wait_for_delayed_systems,
+                place_on_new_course.after(crate::chunk::receive_playable_course),
+            )
+                .run_if(in_state(AppState::Regenerate)),
         );
     }
 }
@@ -48,7 +53,12 @@ fn wait_for_delayed_systems(
     mut commands: Commands,
     mut query: Query<(Entity, &mut CallbackDelay, &Callback)>,
     time: Res<Time>,
+    course: Option<Res<PlayableCourse>>,
 ) {
+    // the course is still being built, the camera stays until it is there
+    if course.is_some() {
+        return;
+    }
     for (entity, mut delay, callback) in &mut query {
         if delay.0.tick(time.delta()).just_finished() {
             commands.run_system(callback.0);
@@ -71,13 +81,26 @@ fn place_regen_camera(
 }
 
 fn regenerate_course(
-    mut golfball: Single<&mut Transform, (With<Golfball>, Without<FlagPole>)>,
-    mut flag_pole: Single<&mut Transform, (With<FlagPole>, Without<Golfball>)>,
-    mut chunk_manager: ResMut<ChunkManager>,
+    mut registry: ResMut<GeneratorRegistry>,
+    thresholds: Res<CourseThresholds>,
     mut commands: Commands,
 ) {
+    // reroll until the course can be played
     let seed = std::time::UNIX_EPOCH.elapsed().unwrap().as_secs() as u32;
-    chunk_manager.replace_generator(&mut commands, Box::new(GrasslandsGenerator::new(seed)));
+    commands.insert_resource(PlayableCourse::spawn(&mut registry, &thresholds, seed));
+}
+
+// moves ball and flag to the new course once it is there
+fn place_on_new_course(
+    mut golfball: Single<&mut Transform, (With<Golfball>, Without<FlagPole>)>,
+    mut flag_pole: Single<&mut Transform, (With<FlagPole>, Without<Golfball>)>,
+    chunk_manager: Res<ChunkManager>,
+    mut placed_on: Local<u32>,
+) {
+    if chunk_manager.course_id() == *placed_on {
+        return;
+    }
+    *placed_on = chunk_manager.course_id();
 
     let [start_x, start_z] = chunk_manager.generator.start();
     let start_y = chunk_manager.generator.height_at(start_x, start_z) + 0.5;
@@ -0,0 +1,38 @@
+use bevy::{
+    color::{Color, palettes::css::RED},
+    prelude::*,
+};
+
+use crate::{club::ClubBag, state::putting::Putting, ui::ui::spawn_nested_text_bundle_with_bundle};
+
+#[derive(Component)]
+pub(super) struct ClubSelectionText;
+
+pub(super) fn spawn_club_selection_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(RED),
+        UiRect::default(),
+        "Driver",
+        (),
+        ClubSelectionText,
+    );
+}
+
+pub(super) fn update_club_selection_ui_system(
+    club_bag: Res<ClubBag>,
+    putting: Option<Res<Putting>>,
+    mut query: Query<&mut Text, With<ClubSelectionText>>,
+) {
+    let club = club_bag.selected();
+    for mut text in &mut query {
+        **text = match &putting {
+            Some(putting) => format!(
+                "{}\nPUTT 0-{}m",
+                club.name,
+                putting.full_power_distance.round()
+            ),
+            None => format!("{}\nLOFT {}-{}°", club.name, club.loft.start, club.loft.end),
+        };
+    }
+}
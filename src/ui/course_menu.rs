@@ -0,0 +1,106 @@
+use bevy::{
+    color::{Color, palettes::css::LIGHT_GREEN},
+    prelude::*,
+};
+
+use crate::{
+    generation::registry::{GeneratorRegistry, GeneratorSelection},
+    state::state::AppState,
+    ui::ui::spawn_nested_text_bundle_with_bundle,
+};
+
+pub struct CourseMenuPlugin;
+impl Plugin for CourseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (course_menu_input_handler, update_course_menu_ui_system).chain(),
+        );
+    }
+}
+
+// The course selection while it is open, the generator under the marker is only chosen on
+// confirm.
+#[derive(Resource)]
+struct CourseMenu {
+    choices: Vec<GeneratorSelection>,
+    highlighted: usize,
+}
+
+#[derive(Component)]
+pub(super) struct CourseMenuContainer;
+#[derive(Component)]
+pub(super) struct CourseMenuText;
+
+pub(super) fn spawn_course_menu_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(LIGHT_GREEN),
+        UiRect::top(px(6)),
+        "COURSE",
+        CourseMenuContainer,
+        CourseMenuText,
+    );
+}
+
+fn course_menu_input_handler(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    menu: Option<ResMut<CourseMenu>>,
+    mut registry: ResMut<GeneratorRegistry>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    let Some(mut menu) = menu else {
+        if keyboard_input.just_pressed(KeyCode::KeyC) {
+            let choices = registry.choices();
+            let highlighted = choices
+                .iter()
+                .position(|choice| *choice == registry.selection)
+                .unwrap_or(0);
+            commands.insert_resource(CourseMenu {
+                choices,
+                highlighted,
+            });
+        }
+        return;
+    };
+
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        menu.highlighted = (menu.highlighted + 1) % menu.choices.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        registry.selection = menu.choices[menu.highlighted].clone();
+        commands.remove_resource::<CourseMenu>();
+        next_state.set(AppState::Regenerate);
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<CourseMenu>();
+    }
+}
+
+fn update_course_menu_ui_system(
+    menu: Option<Res<CourseMenu>>,
+    mut containers: Query<&mut Node, With<CourseMenuContainer>>,
+    mut texts: Query<&mut Text, With<CourseMenuText>>,
+) {
+    for mut node in &mut containers {
+        node.display = match menu {
+            Some(_) => Display::Flex,
+            None => Display::None,
+        };
+    }
+
+    let Some(menu) = menu else {
+        return;
+    };
+
+    let mut info = String::from("COURSE");
+    for (i, choice) in menu.choices.iter().enumerate() {
+        let marker = if i == menu.highlighted { ">" } else { " " };
+        info.push_str(&format!("\n{marker} {}", choice.label()));
+    }
+    info.push_str("\nC: next  ENTER: play  ESC: close");
+    for mut text in &mut texts {
+        **text = info.clone();
+    }
+}
@@ -50,10 +50,9 @@ pub(super) fn update_distances_ui_system(
     }
 }
 
+// measured along the course, around any doglegs
 fn get_current_course_length(chunk_manager: &Res<ChunkManager>) -> f32 {
-    let [hole_x, hole_z] = chunk_manager.generator.hole();
-    let [start_x, start_z] = chunk_manager.generator.start();
-    vec2(start_x, start_z).distance(vec2(hole_x, hole_z))
+    chunk_manager.generator.course_layout().length()
 }
 
 fn get_remaining_course_dist(
@@ -63,7 +62,7 @@ fn get_remaining_course_dist(
     let [hole_x, hole_z] = chunk_manager.generator.hole();
     let hole_y = chunk_manager.generator.height_at(hole_x, hole_z) + 0.5;
     let remaining_dist =
-        vec2(golfball.translation.x, golfball.translation.z).distance(vec2(hole_x, hole_y));
+        vec2(golfball.translation.x, golfball.translation.z).distance(vec2(hole_x, hole_z));
     let height_diff = golfball.translation.y - hole_y;
     (remaining_dist, height_diff)
 }
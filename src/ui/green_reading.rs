@@ -0,0 +1,139 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::light::{NotShadowCaster, NotShadowReceiver};
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+
+use crate::chunk::chunk_manager::ChunkManager;
+use crate::generation::{TerrainGenerator, ZoneType};
+use crate::state::putting::Putting;
+
+// half size of the square around the hole that is scanned for green
+const READING_RADIUS: i32 = 16;
+const ARROW_SPACING: f32 = 1.0;
+// arrows float slightly above the ground so they do not flicker
+const ARROW_LIFT: f32 = 0.03;
+const ARROW_WIDTH: f32 = 0.06;
+// arrow length per unit of slope (m rise per m run) and its limits
+const ARROW_LENGTH_PER_SLOPE: f32 = 12.0;
+const MIN_ARROW_LENGTH: f32 = 0.2;
+const MAX_ARROW_LENGTH: f32 = 0.85;
+// slope at which the arrows turn fully red
+const STEEP_SLOPE: f32 = 0.06;
+
+pub struct GreenReadingPlugin;
+impl Plugin for GreenReadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                show_green_reading.run_if(resource_added::<Putting>),
+                hide_green_reading.run_if(resource_removed::<Putting>),
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct GreenReading;
+
+// downhill direction and steepness (m rise per m run) of the ground
+fn slope_at(generator: &dyn TerrainGenerator, x: f32, z: f32) -> Vec2 {
+    let normal = generator.normal_at(x, z);
+    Vec2::new(normal.x, normal.z) / normal.y
+}
+
+fn show_green_reading(
+    chunk_manager: Res<ChunkManager>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    commands.spawn((
+        GreenReading,
+        Mesh3d(meshes.add(generate_mesh(chunk_manager.generator.as_ref()))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        })),
+        Transform::default(),
+        NotShadowCaster,
+        NotShadowReceiver,
+    ));
+}
+
+fn hide_green_reading(mut commands: Commands, query: Query<Entity, With<GreenReading>>) {
+    for entity in query {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn generate_mesh(generator: &dyn TerrainGenerator) -> Mesh {
+    let [hole_x, hole_z] = generator.hole();
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<[f32; 4]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    for i in -READING_RADIUS..=READING_RADIUS {
+        for j in -READING_RADIUS..=READING_RADIUS {
+            let center = Vec2::new(
+                hole_x.round() + i as f32 * ARROW_SPACING,
+                hole_z.round() + j as f32 * ARROW_SPACING,
+            );
+            if generator.zone_type_at(center.x, center.y) != ZoneType::Green {
+                continue;
+            }
+
+            let slope = slope_at(generator, center.x, center.y);
+            let steepness = slope.length();
+            let forward = slope.normalize_or(Vec2::X);
+            let side = forward.perp() * ARROW_WIDTH;
+            let length =
+                (steepness * ARROW_LENGTH_PER_SLOPE).clamp(MIN_ARROW_LENGTH, MAX_ARROW_LENGTH);
+            let tail = center - forward * length * 0.5;
+            let neck = center + forward * length * 0.1;
+            let tip = center + forward * length * 0.5;
+
+            // shaft quad plus a wider head
+            let outline = [
+                tail - side,
+                tail + side,
+                neck + side,
+                neck - side,
+                neck + side * 2.5,
+                tip,
+                neck - side * 2.5,
+            ];
+            let first = positions.len() as u32;
+            positions.extend(
+                outline
+                    .iter()
+                    .map(|p| [p.x, generator.height_at(p.x, p.y) + ARROW_LIFT, p.y]),
+            );
+            colors.extend([slope_color(steepness).to_f32_array(); 7]);
+            indices.extend([0, 1, 2, 0, 2, 3, 4, 5, 6].map(|index| first + index));
+        }
+    }
+
+    let mut result = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    result.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    result.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    result.insert_indices(Indices::U32(indices));
+    result.with_computed_normals()
+}
+
+// green when flat, through yellow to red on steep slopes
+fn slope_color(steepness: f32) -> LinearRgba {
+    let t = (steepness / STEEP_SLOPE).clamp(0.0, 1.0);
+    let color = if t < 0.5 {
+        Color::srgba(0.2, 0.9, 0.2, 0.8).mix(&Color::srgba(1.0, 0.9, 0.1, 0.8), t * 2.0)
+    } else {
+        Color::srgba(1.0, 0.9, 0.1, 0.8).mix(&Color::srgba(0.95, 0.15, 0.1, 0.8), t * 2.0 - 1.0)
+    };
+    color.to_linear()
+}
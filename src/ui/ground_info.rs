@@ -4,7 +4,7 @@ use bevy::{
 };
 
 use crate::{
-    chunk::chunk_manager::ChunkManager, generation::ZoneType, objects::golfball::Golfball,
+    chunk::chunk_manager::ChunkManager, club::ClubBag, objects::golfball::Golfball,
     ui::ui::spawn_nested_text_bundle_with_bundle,
 };
 
@@ -18,7 +18,7 @@ pub(super) fn spawn_ground_info_ui(builder: &mut ChildSpawnerCommands) {
         builder,
         Color::Srgba(RED),
         UiRect::default(),
-        "Ground Info\n0-100",
+        "Ground Info\nFAIRWAY\n0-100",
         GroundInfoContainer,
         GroundInfoText,
     );
@@ -38,6 +38,7 @@ pub(super) fn hide_ground_info_ui_system(mut query: Query<&mut Node, With<Ground
 
 pub(super) fn update_ground_info_ui_system(
     chunk_manager: Option<Res<ChunkManager>>,
+    club_bag: Res<ClubBag>,
     golfball: Single<&Transform, With<Golfball>>,
     mut query: Query<&mut Text, With<GroundInfoText>>,
 ) {
@@ -46,7 +47,7 @@ pub(super) fn update_ground_info_ui_system(
     };
 
     for mut text in &mut query {
-        let ground_info = get_ground_info(&chunk_manager, &golfball);
+        let ground_info = get_ground_info(&chunk_manager, &club_bag, &golfball);
 
         **text = String::from(format!("Ground Info\n{}", ground_info,));
     }
@@ -54,15 +55,21 @@ pub(super) fn update_ground_info_ui_system(
 
 fn get_ground_info(
     chunk_manager: &Res<ChunkManager>,
+    club_bag: &Res<ClubBag>,
     golfball: &Single<&Transform, With<Golfball>>,
-) -> &'static str {
+) -> String {
     let zone_type = chunk_manager
         .generator
         .zone_type_at(golfball.translation.x, golfball.translation.z);
-    match zone_type {
-        ZoneType::Clean => "98-100",
-        ZoneType::Offtrack => "60-80",
-        ZoneType::Bunker => "20-40",
-        ZoneType::DeadZone => "0",
+    let range = club_bag.selected().penalties.multiplier_range(&zone_type);
+    if range.is_empty() {
+        format!("{}\n0", zone_type.label())
+    } else {
+        format!(
+            "{}\n{}-{}",
+            zone_type.label(),
+            (range.start * 100.0).round(),
+            (range.end * 100.0).round()
+        )
     }
 }
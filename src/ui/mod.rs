@@ -1,7 +1,12 @@
+mod club_selection;
 pub mod course_info;
+mod course_menu;
 mod distances;
 mod flag_direction;
+pub mod green_reading;
 mod ground_info;
+mod penalty_drop;
+mod scorecard;
 pub mod shoot_challenge;
 pub mod trajectory;
 pub mod ui;
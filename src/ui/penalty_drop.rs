@@ -0,0 +1,51 @@
+use bevy::{
+    color::{Color, palettes::css::ORANGE},
+    prelude::*,
+};
+
+use crate::{rules::PenaltyDrop, ui::ui::spawn_nested_text_bundle_with_bundle};
+
+#[derive(Component)]
+pub(super) struct PenaltyDropContainer;
+#[derive(Component)]
+pub(super) struct PenaltyDropText;
+
+pub(super) fn spawn_penalty_drop_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(ORANGE),
+        UiRect::default(),
+        "WATER HAZARD +1",
+        PenaltyDropContainer,
+        PenaltyDropText,
+    );
+}
+
+pub(super) fn update_penalty_drop_ui_system(
+    penalty_drop: Option<Res<PenaltyDrop>>,
+    mut containers: Query<&mut Node, With<PenaltyDropContainer>>,
+    mut texts: Query<&mut Text, With<PenaltyDropText>>,
+) {
+    for mut node in &mut containers {
+        node.display = match penalty_drop {
+            Some(_) => Display::Flex,
+            None => Display::None,
+        };
+    }
+
+    let Some(penalty_drop) = penalty_drop else {
+        return;
+    };
+
+    let mut info = format!("{} +1", penalty_drop.reason.label());
+    for (i, option) in penalty_drop.options.iter().enumerate() {
+        let marker = if i == penalty_drop.selected { ">" } else { " " };
+        info.push_str(&format!("\n{marker} {}", option.kind.label()));
+    }
+    if penalty_drop.options.len() > 1 {
+        info.push_str("\nTAB: change drop");
+    }
+    for mut text in &mut texts {
+        **text = info.clone();
+    }
+}
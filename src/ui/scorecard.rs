@@ -0,0 +1,159 @@
+use bevy::{
+    color::{
+        Color,
+        palettes::css::{GOLD, GREEN},
+    },
+    prelude::*,
+};
+
+use crate::{
+    scorecard::{Scorecard, format_to_par},
+    ui::ui::spawn_nested_text_bundle_with_bundle,
+};
+
+#[derive(Component)]
+pub(super) struct HoleInfoText;
+#[derive(Component)]
+pub(super) struct ScoreSummaryContainer;
+#[derive(Component)]
+pub(super) struct ScoreSummaryText;
+#[derive(Component)]
+pub(super) struct HolePreviewContainer;
+#[derive(Component)]
+pub(super) struct HolePreviewText;
+
+pub(super) fn spawn_hole_info_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(GREEN),
+        UiRect::top(px(3)),
+        "HOLE 1 PAR 4\nSTROKE 0",
+        (),
+        HoleInfoText,
+    );
+}
+
+pub(super) fn spawn_score_summary_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(GOLD),
+        UiRect::default(),
+        "Par",
+        (ScoreSummaryContainer, Visibility::Hidden),
+        ScoreSummaryText,
+    );
+}
+
+pub(super) fn spawn_hole_preview_ui(builder: &mut ChildSpawnerCommands) {
+    spawn_nested_text_bundle_with_bundle(
+        builder,
+        Color::Srgba(GOLD),
+        UiRect::default(),
+        "HOLE 1 PAR 4\n412m UP 8m\nRATING 4.1 SLOPE 113",
+        (HolePreviewContainer, Visibility::Hidden),
+        HolePreviewText,
+    );
+}
+
+pub(super) fn show_score_summary_ui_system(
+    scorecard: Res<Scorecard>,
+    mut containers: Query<&mut Visibility, With<ScoreSummaryContainer>>,
+    mut texts: Query<&mut Text, With<ScoreSummaryText>>,
+) {
+    let Some(hole) = scorecard.current() else {
+        return;
+    };
+
+    let penalties = match hole.penalties {
+        0 => String::new(),
+        1 => String::from(" (1 penalty)"),
+        n => format!(" ({n} penalties)"),
+    };
+    for mut text in &mut texts {
+        **text = format!(
+            "HOLE {} - {}\nPAR {} STROKES {}{penalties}\nRATING {:.1} SLOPE {} DIFF {:+.1}\nTOTAL {} ({})",
+            scorecard.hole_number(),
+            hole.term(),
+            hole.par,
+            hole.total(),
+            hole.rating.rating,
+            hole.rating.slope,
+            hole.differential(),
+            format_to_par(scorecard.total_to_par()),
+            scorecard.total_strokes(),
+        );
+    }
+    for mut visibility in &mut containers {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub(super) fn hide_score_summary_ui_system(
+    mut query: Query<&mut Visibility, With<ScoreSummaryContainer>>,
+) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub(super) fn update_hole_info_ui_system(
+    scorecard: Res<Scorecard>,
+    mut query: Query<&mut Text, With<HoleInfoText>>,
+) {
+    let Some(hole) = scorecard.current() else {
+        return;
+    };
+
+    for mut text in &mut query {
+        **text = format!(
+            "HOLE {} PAR {}\nSTROKE {}",
+            scorecard.hole_number(),
+            hole.par,
+            hole.total()
+        );
+    }
+}
+
+pub(super) fn update_hole_preview_ui_system(
+    scorecard: Res<Scorecard>,
+    mut query: Query<&mut Text, With<HolePreviewText>>,
+) {
+    let Some(hole) = scorecard.current() else {
+        return;
+    };
+
+    let rating = &hole.rating;
+    let (height_label, height_val) = if rating.elevation_change < 0.0 {
+        ("DOWN", -rating.elevation_change)
+    } else {
+        ("UP", rating.elevation_change)
+    };
+    for mut text in &mut query {
+        **text = format!(
+            "HOLE {} PAR {}\n{}m {height_label} {}m\nRATING {:.1} SLOPE {}",
+            scorecard.hole_number(),
+            hole.par,
+            rating.length.round(),
+            height_val.round(),
+            rating.rating,
+            rating.slope
+        );
+    }
+}
+
+// shown during the flyover before the hole is played
+pub(super) fn show_hole_preview_ui_system(
+    mut query: Query<&mut Visibility, With<HolePreviewContainer>>,
+) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub(super) fn hide_hole_preview_ui_system(
+    mut query: Query<&mut Visibility, With<HolePreviewContainer>>,
+) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+}
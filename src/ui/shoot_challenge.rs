@@ -3,7 +3,7 @@ use bevy::{
     prelude::*,
 };
 
-use crate::state::state::AppState;
+use crate::{club::ClubBag, state::state::AppState};
 
 const AIM_CHALLENGE_SPEED: f32 = 0.8;
 
@@ -24,6 +24,7 @@ impl Plugin for ShootChallengePlugin {
                     update_power_cursor_marker,
                     update_power_indicator,
                     update_precision_cursor_marker,
+                    update_precision_indicator,
                 )
                     .run_if(in_state(AppState::Aim)),
             )
@@ -52,6 +53,18 @@ pub struct AimChallengeResource {
     pub precision_marker: Option<f32>,
 }
 
+impl AimChallengeResource {
+    // the power marker once it is set, the moving cursor while it is not and full power
+    // before the challenge started
+    pub fn power_estimate(&self) -> f32 {
+        match self.power_marker {
+            Some(power) => power,
+            None if self.cursor_pos > 0.0 => self.cursor_pos.min(1.0),
+            None => 1.0,
+        }
+    }
+}
+
 #[derive(Component)]
 struct OriginMarker;
 #[derive(Component)]
@@ -62,6 +75,8 @@ struct PowerMarker;
 struct PrecisionMarker;
 #[derive(Component)]
 struct PowerIndicator;
+#[derive(Component)]
+struct PrecisionIndicator;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, States, Default)]
 pub enum AimChallengeState {
@@ -188,6 +203,15 @@ fn update_precision_cursor_marker(
     }
 }
 
+fn update_precision_indicator(
+    club_bag: Res<ClubBag>,
+    mut indicator: Single<&mut Node, With<PrecisionIndicator>>,
+) {
+    let grace = club_bag.selected().precision_grace;
+    indicator.left = _res_unit_to_perc(grace);
+    indicator.right = _res_unit_to_perc(0.8 + grace);
+}
+
 fn spawn_marker<T: Bundle>(builder: &mut ChildSpawnerCommands, marker_type: T) {
     builder.spawn((
         Node {
@@ -234,6 +258,7 @@ fn spawn_gradient(builder: &mut ChildSpawnerCommands) {
 }
 
 fn spawn_precision_indicator(builder: &mut ChildSpawnerCommands) {
+    // the horizontal extent follows the selected club's grace window
     builder.spawn((
         Node {
             width: auto(),
@@ -249,6 +274,7 @@ fn spawn_precision_indicator(builder: &mut ChildSpawnerCommands) {
             ..default()
         },
         BackgroundColor(bevy::prelude::Color::Srgba(RED_600)),
+        PrecisionIndicator,
     ));
 }
 
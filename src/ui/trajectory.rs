@@ -1,23 +1,94 @@
 use crate::camera::ActiveCamera;
+use crate::chunk::chunk_manager::ChunkManager;
+use crate::club::ClubBag;
+use crate::generation::{TerrainGenerator, ZoneType};
 use crate::objects::golfball::Golfball;
+use crate::physics::flight::FlightSimulation;
+use crate::physics::launch::{LaunchConditions, launch_conditions};
+use crate::physics::surface::surface_profile;
+use crate::physics::wind::Wind;
 use crate::state::aim::AimState;
+use crate::state::putting::Putting;
 use crate::state::state::AppState;
-use avian3d::prelude::ColliderDisabled;
+use crate::ui::shoot_challenge::AimChallengeResource;
 use bevy::app::{App, Plugin};
-use bevy::asset::Assets;
-use bevy::color::palettes::css::RED;
+use bevy::asset::{Assets, RenderAssetUsages};
 use bevy::light::{NotShadowCaster, NotShadowReceiver};
-use bevy::mesh::Mesh3d;
+use bevy::mesh::{Indices, Mesh3d, PrimitiveTopology};
 use bevy::pbr::{ExtendedMaterial, MaterialExtension, StandardMaterial};
 use bevy::prelude::*;
 use bevy::render::render_resource::AsBindGroup;
 use bevy::shader::ShaderRef;
-use std::f32::consts::PI;
+use std::f32::consts::FRAC_PI_2;
+
+// same step as the physics schedule
+const PREVIEW_STEP: f32 = 1.0 / 64.0;
+const PREVIEW_MAX_TIME: f32 = 12.0;
+const ARC_HALF_WIDTH: f32 = 0.08;
+const SHADOW_HALF_WIDTH: f32 = 0.15;
+// lift above the ground so draped meshes do not flicker
+const GROUND_LIFT: f32 = 0.05;
+const ROLL_SAMPLE_DISTANCE: f32 = 0.25;
+const RETICLE_RADIUS: f32 = 1.5;
+const PUTT_RETICLE_RADIUS: f32 = 0.3;
 
 pub struct TrajectoryPlugin;
 
+// every part of the preview, despawned together
 #[derive(Component)]
 struct Trajectory;
+#[derive(Component)]
+struct TrajectoryArc;
+#[derive(Component)]
+struct TrajectoryShadow;
+#[derive(Component)]
+struct LandingReticle;
+
+// The inputs the preview was last built from. Rounded, so the wind gusting or the power
+// cursor moving by a hair doesn't simulate the whole flight again.
+#[derive(Component, Default)]
+struct TrajectoryInputs(Option<PreviewKey>);
+
+#[derive(PartialEq)]
+struct PreviewKey {
+    course_id: u32,
+    putting: bool,
+    // centimetres
+    origin: IVec3,
+    // thousandths
+    aim: IVec3,
+    // decimetres per second
+    wind: IVec3,
+    // tenths of m/s, milliradians and rad/s
+    launch: [i32; 5],
+}
+
+impl PreviewKey {
+    fn new(
+        course_id: u32,
+        putting: bool,
+        origin: Vec3,
+        aim: Vec3,
+        wind: Vec3,
+        launch: &LaunchConditions,
+    ) -> Self {
+        let round = |value: Vec3, step: f32| (value / step).round().as_ivec3();
+        PreviewKey {
+            course_id,
+            putting,
+            origin: round(origin, 0.01),
+            aim: round(aim, 0.001),
+            wind: round(wind, 0.1),
+            launch: [
+                (launch.speed * 10.0).round() as i32,
+                (launch.launch_angle * 1000.0).round() as i32,
+                (launch.side_angle * 1000.0).round() as i32,
+                launch.backspin.round() as i32,
+                launch.sidespin.round() as i32,
+            ],
+        }
+    }
+}
 
 impl Plugin for TrajectoryPlugin {
     fn build(&self, app: &mut App) {
@@ -31,50 +102,151 @@ impl Plugin for TrajectoryPlugin {
 }
 
 fn show_trajectory(
-    ball: Single<(&Golfball, &Transform)>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ExtendedMaterial<StandardMaterial, TrajectoryExtension>>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
 ) {
-    let height = 2.0;
-    commands
-        .spawn((
-            Transform::from_xyz(
-                ball.1.translation.x,
-                ball.1.translation.y,
-                ball.1.translation.z,
-            ),
-            NotShadowReceiver,
-            NotShadowCaster,
-            ColliderDisabled,
-            Trajectory,
-        ))
-        .with_child((
-            Mesh3d(meshes.add(Cylinder::new(0.02, height))),
-            MeshMaterial3d(materials.add(ExtendedMaterial {
-                base: StandardMaterial {
-                    base_color: RED.into(),
-                    unlit: true,
-                    alpha_mode: AlphaMode::Blend,
-                    ..Default::default()
-                },
-                extension: TrajectoryExtension {},
-            })),
-            Transform::from_xyz(0.0, height * 0.5, 0.0),
-            NotShadowReceiver,
-            NotShadowCaster,
-            ColliderDisabled,
-        ));
+    commands.spawn((
+        Mesh3d(meshes.add(build_ribbon(&[Vec3::ZERO; 2], ARC_HALF_WIDTH))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                cull_mode: None,
+                ..Default::default()
+            },
+            extension: TrajectoryExtension {},
+        })),
+        Transform::default(),
+        NotShadowReceiver,
+        NotShadowCaster,
+        Trajectory,
+        TrajectoryArc,
+        TrajectoryInputs::default(),
+    ));
+
+    // the arc projected onto the terrain
+    commands.spawn((
+        Mesh3d(meshes.add(build_ribbon(&[Vec3::ZERO; 2], SHADOW_HALF_WIDTH))),
+        MeshMaterial3d(standard_materials.add(StandardMaterial {
+            base_color: Color::srgba(0.0, 0.0, 0.0, 0.3),
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            ..Default::default()
+        })),
+        Transform::default(),
+        NotShadowReceiver,
+        NotShadowCaster,
+        Trajectory,
+        TrajectoryShadow,
+    ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(Annulus::new(0.8, 1.0))),
+        MeshMaterial3d(standard_materials.add(StandardMaterial {
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            ..Default::default()
+        })),
+        Transform::default(),
+        NotShadowReceiver,
+        NotShadowCaster,
+        Trajectory,
+        LandingReticle,
+    ));
 }
 
 fn update_trajectory(
     aim_state: Res<AimState>,
-    mut transform: Single<&mut Transform, (With<Trajectory>, Without<ActiveCamera>)>,
-    camera: Single<&mut Transform, (With<ActiveCamera>, Without<Trajectory>)>,
+    aim_challenge: Option<Res<AimChallengeResource>>,
+    putting: Option<Res<Putting>>,
+    club_bag: Res<ClubBag>,
+    wind: Res<Wind>,
+    chunk_manager: Res<ChunkManager>,
+    golfball: Single<&Transform, (With<Golfball>, Without<LandingReticle>)>,
+    camera: Single<&Transform, (With<ActiveCamera>, Without<LandingReticle>)>,
+    ribbons: Query<
+        (&Mesh3d, Has<TrajectoryArc>),
+        Or<(With<TrajectoryArc>, With<TrajectoryShadow>)>,
+    >,
+    reticle: Single<(&mut Transform, &MeshMaterial3d<StandardMaterial>), With<LandingReticle>>,
+    mut inputs: Single<&mut TrajectoryInputs>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let mut direction = camera.clone();
-    direction.rotate_local_x((aim_state.height - 90.0) / 180.0 * PI);
-    transform.rotation = direction.rotation;
+    let generator = chunk_manager.generator.as_ref();
+    let club = club_bag.selected();
+    let origin = golfball.translation;
+
+    // the shot as it would come off a centered strike at the current power
+    let lie = club
+        .penalties
+        .expected_multiplier(&generator.zone_type_at(origin.x, origin.z));
+    let mut power = aim_challenge.map_or(1.0, |challenge| challenge.power_estimate());
+    if let Some(putting) = &putting {
+        power = putting.scale_power(power, club);
+    }
+    let launch = launch_conditions(club, power, aim_state.height, 0.0, lie);
+    let aim = camera.forward().as_vec3();
+
+    // the meshes and the simulation behind them only change with their inputs
+    let key = PreviewKey::new(
+        chunk_manager.course_id(),
+        putting.is_some(),
+        origin,
+        aim,
+        wind.velocity(),
+        &launch,
+    );
+    if inputs.0.as_ref() == Some(&key) {
+        return;
+    }
+    inputs.0 = Some(key);
+
+    let path = if putting.is_some() {
+        roll_path(generator, origin, &launch, aim)
+    } else {
+        FlightSimulation::new(
+            origin,
+            launch.linear_velocity(aim),
+            launch.angular_velocity(aim),
+            wind.velocity(),
+        )
+        .trace(PREVIEW_STEP, PREVIEW_MAX_TIME, |x, z| {
+            generator.height_at(x, z)
+        })
+    };
+    let shadow: Vec<Vec3> = path
+        .iter()
+        .map(|p| p.with_y(generator.height_at(p.x, p.z) + GROUND_LIFT))
+        .collect();
+
+    for (mesh, is_arc) in &ribbons {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = if is_arc {
+                build_ribbon(&path, ARC_HALF_WIDTH)
+            } else {
+                build_ribbon(&shadow, SHADOW_HALF_WIDTH)
+            };
+        }
+    }
+
+    let landing = *path.last().unwrap_or(&origin);
+    let (mut transform, material) = reticle.into_inner();
+    let radius = if putting.is_some() {
+        PUTT_RETICLE_RADIUS
+    } else {
+        RETICLE_RADIUS
+    };
+    *transform = Transform::from_translation(landing + Vec3::Y * GROUND_LIFT)
+        .with_rotation(Quat::from_rotation_x(-FRAC_PI_2))
+        .with_scale(Vec3::splat(radius));
+    if let Some(material) = materials.get_mut(&material.0) {
+        material.base_color = zone_color(&generator.zone_type_at(landing.x, landing.z));
+    }
 }
 
 fn hide_trajectory(mut commands: Commands, query: Query<Entity, With<Trajectory>>) {
@@ -83,6 +255,75 @@ fn hide_trajectory(mut commands: Commands, query: Query<Entity, With<Trajectory>
     }
 }
 
+// a putt rolls out along its start line until the green's rolling resistance stops it
+fn roll_path(
+    generator: &dyn TerrainGenerator,
+    origin: Vec3,
+    launch: &LaunchConditions,
+    aim: Vec3,
+) -> Vec<Vec3> {
+    let velocity = launch.linear_velocity(aim);
+    let direction = velocity.with_y(0.0).normalize_or_zero();
+    let rolling_resistance = surface_profile(&ZoneType::Green).rolling_resistance;
+    let distance = launch.speed * launch.speed / (2.0 * rolling_resistance);
+
+    let samples = (distance / ROLL_SAMPLE_DISTANCE).ceil() as usize + 1;
+    (0..=samples)
+        .map(|i| {
+            let p = origin + direction * distance * i as f32 / samples as f32;
+            p.with_y(generator.height_at(p.x, p.z) + GROUND_LIFT)
+        })
+        .collect()
+}
+
+// flat strip along `points`, facing up
+fn build_ribbon(points: &[Vec3], half_width: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut uvs = Vec::with_capacity(points.len() * 2);
+    for (i, point) in points.iter().enumerate() {
+        let before = points[i.saturating_sub(1)];
+        let after = points[(i + 1).min(points.len() - 1)];
+        let side = (after - before)
+            .with_y(0.0)
+            .cross(Vec3::Y)
+            .normalize_or(Vec3::X)
+            * half_width;
+        let v = i as f32 / points.len().max(2) as f32;
+        positions.extend([(point - side).to_array(), (point + side).to_array()]);
+        uvs.extend([[0.0, v], [1.0, v]]);
+    }
+
+    let mut indices = Vec::new();
+    for i in 0..points.len().saturating_sub(1) as u32 {
+        let a = i * 2;
+        indices.extend([a, a + 1, a + 2, a + 1, a + 3, a + 2]);
+    }
+
+    let mut result = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    result.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    result.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    result.insert_indices(Indices::U32(indices));
+    result
+}
+
+fn zone_color(zone_type: &ZoneType) -> Color {
+    match zone_type {
+        ZoneType::DeadZone => Color::srgba(0.1, 0.4, 1.0, 0.8),
+        ZoneType::Tee | ZoneType::Fairway => Color::srgba(0.3, 1.0, 0.3, 0.8),
+        ZoneType::Green | ZoneType::Fringe => Color::srgba(0.8, 1.0, 0.8, 0.8),
+        ZoneType::FirstCut => Color::srgba(0.7, 1.0, 0.2, 0.8),
+        ZoneType::Rough => Color::srgba(1.0, 0.75, 0.1, 0.8),
+        ZoneType::Offtrack => Color::srgba(1.0, 0.45, 0.1, 0.8),
+        ZoneType::Bunker => Color::srgba(1.0, 0.9, 0.4, 0.8),
+        ZoneType::WasteArea => Color::srgba(0.85, 0.75, 0.55, 0.8),
+        ZoneType::Rock => Color::srgba(0.5, 0.5, 0.5, 0.8),
+        ZoneType::Snow => Color::srgba(0.9, 0.95, 1.0, 0.8),
+    }
+}
+
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
 struct TrajectoryExtension {}
 
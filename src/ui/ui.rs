@@ -1,13 +1,24 @@
 use crate::{
+    club::ClubBag,
+    scorecard::Scorecard,
+    state::putting::Putting,
     state::state::AppState,
     ui::{
+        club_selection::{spawn_club_selection_ui, update_club_selection_ui_system},
         course_info::{CourseFlagPlugin, spawn_course_info},
+        course_menu::{CourseMenuPlugin, spawn_course_menu_ui},
         distances::{spawn_distances_ui, update_distances_ui_system},
         flag_direction::FlagDirectionUiPlugin,
         ground_info::{
             hide_ground_info_ui_system, show_ground_info_ui_system, spawn_ground_info_ui,
             update_ground_info_ui_system,
         },
+        penalty_drop::{spawn_penalty_drop_ui, update_penalty_drop_ui_system},
+        scorecard::{
+            hide_hole_preview_ui_system, hide_score_summary_ui_system, show_hole_preview_ui_system,
+            show_score_summary_ui_system, spawn_hole_info_ui, spawn_hole_preview_ui,
+            spawn_score_summary_ui, update_hole_info_ui_system, update_hole_preview_ui_system,
+        },
         wind_indicator::WindIndicatorPlugin,
     },
 };
@@ -20,16 +31,43 @@ pub struct UiPlugin;
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, spawn_layout)
-            .add_plugins((CourseFlagPlugin, WindIndicatorPlugin, FlagDirectionUiPlugin))
+            .add_plugins((
+                CourseFlagPlugin,
+                WindIndicatorPlugin,
+                FlagDirectionUiPlugin,
+                CourseMenuPlugin,
+            ))
             .add_systems(Update, update_distances_ui_system)
             .add_systems(
-                OnEnter(AppState::Aim),
-                (
-                    show_ground_info_ui_system,
-                    update_ground_info_ui_system,
+                Update,
+                update_club_selection_ui_system.run_if(
+                    resource_changed::<ClubBag>
+                        .or(resource_added::<Putting>)
+                        .or(resource_removed::<Putting>),
                 ),
             )
-            .add_systems(OnExit(AppState::Aim), hide_ground_info_ui_system);
+            .add_systems(OnEnter(AppState::Aim), show_ground_info_ui_system)
+            .add_systems(
+                Update,
+                update_ground_info_ui_system.run_if(in_state(AppState::Aim)),
+            )
+            .add_systems(OnExit(AppState::Aim), hide_ground_info_ui_system)
+            .add_systems(
+                Update,
+                (update_hole_info_ui_system, update_hole_preview_ui_system)
+                    .run_if(resource_changed::<Scorecard>),
+            )
+            .add_systems(
+                OnEnter(AppState::PresentCourse),
+                show_hole_preview_ui_system,
+            )
+            .add_systems(OnExit(AppState::PresentCourse), hide_hole_preview_ui_system)
+            .add_systems(
+                OnEnter(AppState::PostScore),
+                show_score_summary_ui_system.after(crate::scorecard::finish_hole),
+            )
+            .add_systems(OnExit(AppState::PostScore), hide_score_summary_ui_system)
+            .add_systems(Update, update_penalty_drop_ui_system);
     }
 }
 
@@ -68,6 +106,12 @@ fn spawn_layout(mut commands: Commands) {
 
                             // hole info / distances
                             spawn_distances_ui(builder);
+
+                            // hole number / par / strokes
+                            spawn_hole_info_ui(builder);
+
+                            // generator selection, only while open
+                            spawn_course_menu_ui(builder);
                         });
 
                     // wind display
@@ -88,13 +132,17 @@ fn spawn_layout(mut commands: Commands) {
                     ..default()
                 })
                 .with_children(|builder| {
-                    // selected drive
-                    spawn_nested_text_bundle(
-                        builder,
-                        Color::Srgba(RED),
-                        UiRect::default(),
-                        "DriveSelection",
-                    );
+                    // selected club
+                    spawn_club_selection_ui(builder);
+
+                    // hole result, only during post score
+                    spawn_score_summary_ui(builder);
+
+                    // par and rating, only during the flyover
+                    spawn_hole_preview_ui(builder);
+
+                    // drop options after a penalty
+                    spawn_penalty_drop_ui(builder);
 
                     // ground info
                     spawn_ground_info_ui(builder);
@@ -7,18 +7,25 @@ use bevy::scene::SceneInstanceReady;
 use bevy::ui::Node;
 
 use crate::camera::ActiveCamera;
+use crate::physics::wind::Wind;
 
 const GLTF_PATH: &str = "model/wind_indicator.glb";
 const WIND_INDICATOR_LAYER: RenderLayers = RenderLayers::layer(1);
 
 #[derive(Component)]
 struct WindIndicator;
+#[derive(Component)]
+struct WindIndicatorCamera;
+#[derive(Component)]
+struct WindSpeedText;
 
 pub(super) struct WindIndicatorPlugin;
 impl Plugin for WindIndicatorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup)
-            .add_systems(Update, update_wind_indicator_position);
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (update_wind_indicator_position, update_wind_speed_text),
+        );
     }
 }
 fn setup(
@@ -47,6 +54,7 @@ fn setup(
             },
             Transform::from_xyz(0.0, 0.0, -5.0).looking_at(Vec3::splat(0.0), Vec3::Y),
             WIND_INDICATOR_LAYER,
+            WindIndicatorCamera,
         ))
         .id();
 
@@ -73,6 +81,18 @@ fn setup(
         },
         ViewportNode::new(camera),
     ));
+
+    // wind speed below the indicator
+    commands.spawn((
+        Text::new("WIND 0.0m/s"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(200),
+            right: px(12),
+            ..default()
+        },
+        WindSpeedText,
+    ));
 }
 
 // https://github.com/bevyengine/bevy/issues/12461
@@ -95,13 +115,31 @@ fn apply_render_layers_to_children(
 }
 
 fn update_wind_indicator_position(
-    mut set: ParamSet<(
-        Single<&mut Transform, (With<Camera3d>, With<ActiveCamera>)>,
-        Single<&mut Transform, With<WindIndicator>>,
-    )>,
+    wind: Res<Wind>,
+    active_camera: Single<&Transform, (With<Camera3d>, With<ActiveCamera>)>,
+    indicator_camera: Single<&Transform, (With<WindIndicatorCamera>, Without<ActiveCamera>)>,
+    mut wind_indicator: Single<
+        &mut Transform,
+        (
+            With<WindIndicator>,
+            Without<ActiveCamera>,
+            Without<WindIndicatorCamera>,
+        ),
+    >,
 ) {
-    let active_camera_rotation = set.p0().rotation;
-    let wind_indicator = set.p1();
-    let mut transform = wind_indicator.into_inner();
-    transform.rotation = active_camera_rotation;
+    let velocity = wind.velocity();
+    if velocity.xz().length() <= f32::EPSILON {
+        return;
+    }
+
+    // face the model downwind, as seen from the active camera
+    let wind_rotation = Transform::default()
+        .looking_to(vec3(velocity.x, 0.0, velocity.z), Vec3::Y)
+        .rotation;
+    wind_indicator.rotation =
+        indicator_camera.rotation * active_camera.rotation.inverse() * wind_rotation;
+}
+
+fn update_wind_speed_text(wind: Res<Wind>, mut text: Single<&mut Text, With<WindSpeedText>>) {
+    text.0 = format!("WIND {:.1}m/s", wind.speed());
 }